@@ -1,7 +1,11 @@
-use crate::{AppState, ChatMessage};
+use crate::{tokenizer, AppState, ChatMessage, Settings};
+use async_trait::async_trait;
+use futures_util::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ModelInfo {
@@ -10,347 +14,1305 @@ pub struct ModelInfo {
     pub provider: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AnthropicRequest {
-    model: String,
-    messages: Vec<AnthropicMessage>,
-    max_tokens: u32,
-    system: Option<String>,
+/// A local tool the model can invoke, described to the provider in its
+/// native function-calling schema. `parameters` is a JSON Schema object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AnthropicMessage {
-    role: String,
-    content: String,
+/// A single invocation the model asked for; `arguments` is the parsed
+/// JSON the model produced for `parameters`.
+#[derive(Debug, Clone)]
+struct ToolCall {
+    id: String,
+    name: String,
+    arguments: serde_json::Value,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AnthropicResponse {
-    content: Vec<AnthropicContent>,
-    usage: AnthropicUsage,
+/// What a provider returned for one turn: either a finished answer, or one
+/// or more tool calls the agentic loop must dispatch before continuing.
+enum ChatOutcome {
+    Text(String),
+    ToolCalls(Vec<ToolCall>),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AnthropicContent {
-    #[serde(rename = "type")]
-    content_type: String,
-    text: Option<String>,
+/// Token counts for a single call, normalized from whatever the provider
+/// calls them (`input_tokens`/`output_tokens`, `prompt_tokens`/
+/// `completion_tokens`, `promptTokenCount`/`candidatesTokenCount`, ...).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input: u32,
+    pub output: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AnthropicUsage {
-    #[serde(rename = "input_tokens")]
-    input_tokens: u32,
-    #[serde(rename = "output_tokens")]
-    output_tokens: u32,
+impl TokenUsage {
+    fn accumulate(&mut self, other: &TokenUsage) {
+        self.input += other.input;
+        self.output += other.output;
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct OpenAIRequest {
-    model: String,
-    messages: Vec<OpenAIMessage>,
-    max_tokens: Option<u32>,
+/// USD price per million tokens for a model: the first entry whose key occurs
+/// anywhere in the model id wins, so more specific keys are listed before
+/// shorter ones they contain. Unknown models cost nothing to estimate rather than
+/// failing the request; the UI can flag a zero estimate as "unpriced".
+const PRICE_TABLE_PER_MILLION: &[(&str, f64, f64)] = &[
+    ("claude-opus", 15.0, 75.0),
+    ("claude-sonnet", 3.0, 15.0),
+    ("claude-haiku", 0.8, 4.0),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4o", 2.5, 10.0),
+    ("gpt-4", 30.0, 60.0),
+    ("gpt-3.5", 0.5, 1.5),
+    ("o1-mini", 1.1, 4.4),
+    ("o1", 15.0, 60.0),
+    ("gemini-1.5-pro", 1.25, 5.0),
+    ("gemini-1.5-flash", 0.075, 0.3),
+    ("gemini", 0.075, 0.3),
+    ("grok", 5.0, 15.0),
+    ("sonar", 1.0, 1.0),
+];
+
+fn price_per_million(model: &str) -> (f64, f64) {
+    PRICE_TABLE_PER_MILLION
+        .iter()
+        .find(|(prefix, _, _)| model.contains(prefix))
+        .map(|(_, input, output)| (*input, *output))
+        .unwrap_or((0.0, 0.0))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct OpenAIMessage {
-    role: String,
-    content: String,
+fn estimated_cost_usd(model: &str, usage: &TokenUsage) -> f64 {
+    let (input_price, output_price) = price_per_million(model);
+    (usage.input as f64 / 1_000_000.0) * input_price + (usage.output as f64 / 1_000_000.0) * output_price
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct OpenAIResponse {
-    choices: Vec<OpenAIChoice>,
-    usage: OpenAIUsage,
+/// Running per-model and per-session token totals, held in memory only and
+/// reset on restart (unlike `AppState::messages`, which is reloaded from disk).
+#[derive(Debug, Clone, Default)]
+pub struct UsageStats {
+    pub per_model: BTreeMap<String, TokenUsage>,
+    pub per_session: BTreeMap<String, TokenUsage>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct OpenAIChoice {
-    message: OpenAIMessageResponse,
+/// A chunk of streamed text pushed to the frontend via a Tauri event.
+#[derive(Debug, Clone, Serialize)]
+struct ChatToken<'a> {
+    id: &'a str,
+    delta: &'a str,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct OpenAIMessageResponse {
-    content: String,
+/// A tool invocation surfaced to the frontend as it happens, so the UI can
+/// show "calling get_current_time..." instead of going quiet mid-stream.
+#[derive(Debug, Clone, Serialize)]
+struct ToolCallEvent<'a> {
+    id: &'a str,
+    name: &'a str,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct OpenAIUsage {
-    #[serde(rename = "prompt_tokens")]
-    prompt_tokens: u32,
-    #[serde(rename = "completion_tokens")]
-    completion_tokens: u32,
-}
-
-fn get_model_provider(model: &str) -> &'static str {
-    if model.starts_with("claude-") {
-        "anthropic"
-    } else if model.starts_with("gpt-") || model.starts_with("o1") || model.starts_with("o3") {
-        "openai"
-    } else if model.starts_with("gemini-") {
-        "google"
-    } else if model.starts_with("grok-") {
-        "xai"
-    } else if model.starts_with("perplexity-") {
-        "perplexity"
-    } else {
-        "anthropic"
+/// Emits incremental tokens to the frontend while a stream is in flight.
+/// Owned (rather than borrowed) so it can be threaded through the
+/// `LlmClient` trait object without fighting lifetimes.
+#[derive(Clone)]
+struct StreamEmitter {
+    app_handle: AppHandle,
+    event_name: String,
+    id: String,
+}
+
+impl StreamEmitter {
+    fn emit(&self, delta: &str) {
+        let _ = self.app_handle.emit(&self.event_name, ChatToken { id: &self.id, delta });
+    }
+
+    fn emit_tool_call(&self, call_id: &str, name: &str) {
+        let _ = self.app_handle.emit(&format!("{}-tool-call", self.event_name), ToolCallEvent { id: call_id, name });
     }
 }
 
-#[tauri::command]
-pub async fn get_models(state: State<'_, AppState>) -> Result<Vec<ModelInfo>, String> {
-    let api_keys = {
-        let settings = state.settings.lock().map_err(|e| e.to_string())?;
-        SettingsApiKeys {
-            anthropic: settings.anthropic_api_key.clone(),
-            openai: settings.openai_api_key.clone(),
-            google: settings.google_api_key.clone(),
-            xai: settings.xai_api_key.clone(),
-            perplexity: settings.perplexity_api_key.clone(),
+/// One turn of conversation history, in a shape shared by every provider.
+/// Tool calls/results get mapped to each provider's native representation
+/// (Anthropic `tool_use`/`tool_result` blocks, OpenAI `tool_calls`/`tool`
+/// messages, Google `functionCall`/`functionResponse` parts) at the call site.
+#[derive(Debug, Clone)]
+enum Turn {
+    User(String),
+    Assistant(String),
+    ToolCalls(Vec<ToolCall>),
+    ToolResult { call_id: String, name: String, content: String },
+}
+
+/// Total context window budgeted for a single turn, shared across the
+/// system prompt, carried-forward history, and the new user message. Kept
+/// conservative rather than per-model-accurate, since the providers here
+/// span wildly different real context sizes.
+const MAX_CONTEXT_TOKENS: usize = 16_000;
+
+/// Tokens reserved for the model's reply, matching the `max_tokens` every
+/// `LlmClient` impl requests.
+const RESERVED_COMPLETION_TOKENS: usize = 4_096;
+
+/// Minimum cosine similarity for a memory to be considered relevant enough
+/// to inject into the system prompt.
+const SEMANTIC_SIMILARITY_THRESHOLD: f32 = 0.2;
+
+/// Caps the number of tool-call round trips in a single `chat`/`chat_stream`
+/// invocation so a model that keeps calling tools can't loop forever.
+const MAX_TOOL_STEPS: usize = 8;
+
+/// Builds the structured history to send to the provider: memories relevant
+/// to the new message are folded into the system prompt instead of being
+/// spliced into the user turn, and as many of the most recent
+/// `state.messages` as fit the remaining token budget are carried forward,
+/// oldest-first, so providers get their native multi-turn handling. The
+/// oldest included message is truncated from its start rather than dropped
+/// whole if it would otherwise blow the budget.
+///
+/// Memories are ranked by embedding cosine similarity when `openai_api_key`
+/// is configured and at least one memory has a cached embedding; otherwise
+/// this falls back to the keyword-substring filter.
+async fn build_history(
+    state: &AppState,
+    model: &str,
+    new_message: &str,
+    system_prompt: Option<&str>,
+    openai_api_key: &str,
+) -> Result<(Vec<Turn>, Option<String>), String> {
+    let (messages, memories) = {
+        let messages = state.messages.lock().map_err(|e| e.to_string())?;
+        let memories = state.memories.lock().map_err(|e| e.to_string())?;
+        (messages.clone(), memories.clone())
+    };
+
+    let has_embeddings = memories.iter().any(|m| m.embedding.is_some());
+    let semantic_relevant: Vec<&crate::MemoryItem> = if !openai_api_key.is_empty() && has_embeddings {
+        match crate::embeddings::embed_openai(openai_api_key, new_message).await {
+            Ok(query_vector) => {
+                let mut scored: Vec<(&crate::MemoryItem, f32)> = memories
+                    .iter()
+                    .filter_map(|m| {
+                        m.embedding
+                            .as_ref()
+                            .map(|e| (m, crate::embeddings::cosine_similarity(&query_vector, e)))
+                    })
+                    .filter(|(_, score)| *score >= SEMANTIC_SIMILARITY_THRESHOLD)
+                    .collect();
+                scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+                scored.into_iter().take(5).map(|(m, _)| m).collect()
+            }
+            Err(e) => {
+                println!("Failed to embed query, falling back to keyword search: {}", e);
+                Vec::new()
+            }
         }
+    } else {
+        Vec::new()
     };
 
-    let mut models = Vec::new();
-    let client = Client::new();
+    let relevant: Vec<&crate::MemoryItem> = if !semantic_relevant.is_empty() {
+        semantic_relevant
+    } else {
+        let query = extract_keywords(new_message);
+        if query.is_empty() {
+            Vec::new()
+        } else {
+            memories
+                .iter()
+                .filter(|m| {
+                    m.title.to_lowercase().contains(&query)
+                        || m.tags.iter().any(|t| t.to_lowercase().contains(&query))
+                        || m.entities.iter().any(|e| e.to_lowercase().contains(&query))
+                })
+                .take(5)
+                .collect()
+        }
+    };
 
-    if !api_keys.anthropic.is_empty() {
-        match fetch_anthropic_models(&client, &api_keys.anthropic).await {
-            Ok(m) => models.extend(m),
-            Err(e) => println!("Failed to fetch Anthropic models: {}", e),
+    let mut memory_context = String::new();
+    if !relevant.is_empty() {
+        memory_context.push_str("Relevant memories:\n");
+        for memory in &relevant {
+            memory_context.push_str(&format!("### {}\n{}\n\n", memory.title, memory.content));
         }
     }
 
-    if !api_keys.openai.is_empty() {
-        match fetch_openai_models(&client, &api_keys.openai).await {
-            Ok(m) => models.extend(m),
-            Err(e) => println!("Failed to fetch OpenAI models: {}", e),
+    let memory_context = if memory_context.is_empty() { None } else { Some(memory_context) };
+
+    let mut budget = MAX_CONTEXT_TOKENS.saturating_sub(RESERVED_COMPLETION_TOKENS);
+    budget = budget.saturating_sub(system_prompt.map(|s| tokenizer::count_tokens(model, s)).unwrap_or(0));
+    budget = budget.saturating_sub(memory_context.as_deref().map(|c| tokenizer::count_tokens(model, c)).unwrap_or(0));
+    budget = budget.saturating_sub(tokenizer::count_tokens(model, new_message));
+
+    let mut included: Vec<Turn> = Vec::new();
+    for m in messages.iter().rev() {
+        let tokens = tokenizer::count_tokens(model, &m.content);
+        let turn = if tokens <= budget {
+            budget -= tokens;
+            if m.role == "assistant" {
+                Turn::Assistant(m.content.clone())
+            } else {
+                Turn::User(m.content.clone())
+            }
+        } else if budget > 0 {
+            let truncated = tokenizer::truncate(model, &m.content, budget, tokenizer::TruncateDirection::Start);
+            budget = 0;
+            if m.role == "assistant" {
+                Turn::Assistant(truncated)
+            } else {
+                Turn::User(truncated)
+            }
+        } else {
+            break;
+        };
+        included.push(turn);
+    }
+    included.reverse();
+    included.push(Turn::User(new_message.to_string()));
+
+    Ok((included, memory_context))
+}
+
+/// The local tools exposed to every provider, each dispatched in
+/// `dispatch_tool` to the matching app command.
+fn tool_definitions() -> Vec<ToolDefinition> {
+    vec![
+        ToolDefinition {
+            name: "get_current_time".to_string(),
+            description: "Get the current UTC date and time.".to_string(),
+            parameters: serde_json::json!({ "type": "object", "properties": {} }),
+        },
+        ToolDefinition {
+            name: "save_memory".to_string(),
+            description: "Save a fact, preference, or event worth remembering for future conversations.".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string", "description": "Short title for the memory." },
+                    "content": { "type": "string", "description": "The memory content." },
+                    "memory_type": {
+                        "type": "string",
+                        "description": "One of WORK, LEARNING, RELATIONSHIP, or general.",
+                    },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                },
+                "required": ["title", "content"],
+            }),
+        },
+        ToolDefinition {
+            name: "search_memories".to_string(),
+            description: "Search saved memories by keyword, optionally filtered by memory type.".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "memory_type": { "type": "string" },
+                },
+                "required": ["query"],
+            }),
+        },
+        ToolDefinition {
+            name: "get_work_items".to_string(),
+            description: "List the user's tracked work items (id, title, status).".to_string(),
+            parameters: serde_json::json!({ "type": "object", "properties": {} }),
+        },
+        ToolDefinition {
+            name: "create_new_session".to_string(),
+            description: "Start a new, separate conversation session with the given name.".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": { "name": { "type": "string" } },
+                "required": ["name"],
+            }),
+        },
+    ]
+}
+
+/// Dispatches a tool call to the matching app command. Runs against the
+/// same `AppState`/`Settings` the rest of the app uses (fetched from
+/// `app_handle` rather than threaded through as a parameter) so tool calls
+/// observe and mutate the same memories/sessions as the UI does.
+async fn dispatch_tool(app_handle: &AppHandle, name: &str, arguments: &serde_json::Value) -> Result<String, String> {
+    use tauri::Manager;
+
+    match name {
+        "get_current_time" => Ok(chrono::Utc::now().to_rfc3339()),
+        "save_memory" => {
+            let title = arguments.get("title").and_then(|v| v.as_str()).ok_or("Missing required field: title")?;
+            let content = arguments.get("content").and_then(|v| v.as_str()).ok_or("Missing required field: content")?;
+            let memory_type = arguments.get("memory_type").and_then(|v| v.as_str()).unwrap_or("general");
+            let tags = arguments
+                .get("tags")
+                .and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|t| t.as_str().map(str::to_string)).collect())
+                .unwrap_or_default();
+
+            let memory = crate::MemoryItem {
+                id: format!("mem-{}", chrono::Utc::now().timestamp_millis()),
+                title: title.to_string(),
+                content: content.to_string(),
+                memory_type: memory_type.to_string(),
+                timestamp: chrono::Utc::now().timestamp(),
+                tags,
+                entities: Vec::new(),
+                confidence: 0.8,
+                embedding: None,
+            };
+
+            let state = app_handle.state::<AppState>();
+            crate::memory::save_memory(state, memory).await?;
+            Ok(format!("Saved memory \"{}\"", title))
+        }
+        "search_memories" => {
+            let query = arguments.get("query").and_then(|v| v.as_str()).ok_or("Missing required field: query")?.to_string();
+            let memory_type = arguments.get("memory_type").and_then(|v| v.as_str()).map(str::to_string);
+            let results = crate::memory::search_memories(query, memory_type, None)?;
+            serde_json::to_string(&results).map_err(|e| e.to_string())
+        }
+        "get_work_items" => {
+            let items = crate::memory::get_work_items()?;
+            serde_json::to_string(&items).map_err(|e| e.to_string())
+        }
+        "create_new_session" => {
+            let name = arguments.get("name").and_then(|v| v.as_str()).ok_or("Missing required field: name")?.to_string();
+            let session = crate::session::create_new_session(name)?;
+            serde_json::to_string(&session).map_err(|e| e.to_string())
         }
+        _ => Err(format!("Unknown tool: {}", name)),
     }
+}
 
-    if !api_keys.google.is_empty() {
-        match fetch_google_models(&client, &api_keys.google).await {
-            Ok(m) => models.extend(m),
-            Err(e) => println!("Failed to fetch Google models: {}", e),
+/// Looks up the result of an identical earlier call (same name and
+/// arguments) already present in `history`, so a model that re-issues the
+/// same tool call doesn't trigger a redundant (and possibly side-effecting)
+/// re-execution.
+fn cached_tool_result(history: &[Turn], call: &ToolCall) -> Option<String> {
+    history.iter().enumerate().find_map(|(i, turn)| {
+        let Turn::ToolCalls(calls) = turn else { return None };
+        let prior = calls.iter().find(|c| c.name == call.name && c.arguments == call.arguments)?;
+        history[i..].iter().find_map(|t| match t {
+            Turn::ToolResult { call_id, content, .. } if *call_id == prior.id => Some(content.clone()),
+            _ => None,
+        })
+    })
+}
+
+/// Runs the agentic loop: ask the provider, and if it comes back with tool
+/// calls instead of text, dispatch each one, feed the results back in, and
+/// ask again — until it returns plain text or `MAX_TOOL_STEPS` is exceeded.
+async fn run_tool_loop(
+    app_handle: &AppHandle,
+    client: &dyn LlmClient,
+    model: &str,
+    history: &mut Vec<Turn>,
+    system_prompt: Option<String>,
+    tools: &[ToolDefinition],
+    emit: Option<StreamEmitter>,
+) -> Result<(String, TokenUsage), String> {
+    if !tools.is_empty() && !client.supports_tools() {
+        return Err(format!("Model \"{}\" does not support tool/function calling", model));
+    }
+
+    let mut steps = 0usize;
+    let mut total_usage = TokenUsage::default();
+    loop {
+        let (outcome, usage) = client.chat(model, history, system_prompt.clone(), tools, emit.clone()).await?;
+        total_usage.accumulate(&usage);
+
+        match outcome {
+            ChatOutcome::Text(text) => return Ok((text, total_usage)),
+            ChatOutcome::ToolCalls(calls) => {
+                steps += 1;
+                if steps > MAX_TOOL_STEPS {
+                    return Err(format!("Exceeded maximum tool-call steps ({})", MAX_TOOL_STEPS));
+                }
+
+                let cached_results: Vec<Option<String>> = calls.iter().map(|call| cached_tool_result(history, call)).collect();
+
+                history.push(Turn::ToolCalls(calls.clone()));
+                for (call, cached) in calls.iter().zip(cached_results) {
+                    if let Some(emitter) = &emit {
+                        emitter.emit_tool_call(&call.id, &call.name);
+                    }
+                    let result = match cached {
+                        Some(cached) => cached,
+                        None => dispatch_tool(app_handle, &call.name, &call.arguments).await.unwrap_or_else(|e| e),
+                    };
+                    history.push(Turn::ToolResult {
+                        call_id: call.id.clone(),
+                        name: call.name.clone(),
+                        content: result,
+                    });
+                }
+            }
         }
     }
+}
 
-    if !api_keys.xai.is_empty() {
-        match fetch_xai_models(&client, &api_keys.xai).await {
-            Ok(m) => models.extend(m),
-            Err(e) => println!("Failed to fetch xAI models: {}", e),
+/// Reads an SSE byte stream line by line, invoking `on_data` with the payload
+/// of each `data: ...` frame. Stops cleanly on the `[DONE]` sentinel or when
+/// the underlying stream ends.
+async fn read_sse_stream<F>(response: reqwest::Response, mut on_data: F) -> Result<(), String>
+where
+    F: FnMut(&str),
+{
+    let mut stream = response.bytes_stream();
+    // Raw bytes, not a `String` — a multi-byte UTF-8 character can be split
+    // across an HTTP chunk boundary, and decoding each chunk independently
+    // with `from_utf8_lossy` would replace both halves with U+FFFD before
+    // they're ever reunited.
+    let mut buf: Vec<u8> = Vec::new();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| e.to_string())?;
+        buf.extend_from_slice(&chunk);
+
+        while let Some(pos) = buf.iter().position(|&b| b == b'\n') {
+            let line_bytes: Vec<u8> = buf.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line_bytes);
+            let line = line.trim_end_matches(['\r', '\n']);
+
+            let Some(data) = line.strip_prefix("data: ") else {
+                continue;
+            };
+            if data == "[DONE]" {
+                return Ok(());
+            }
+            on_data(data);
         }
     }
 
-    if !api_keys.perplexity.is_empty() {
-        match fetch_perplexity_models(&client, &api_keys.perplexity).await {
-            Ok(m) => models.extend(m),
-            Err(e) => println!("Failed to fetch Perplexity models: {}", e),
+    Ok(())
+}
+
+/// One chat provider, implemented either against a hardcoded API (Anthropic,
+/// Google, Perplexity) or generically against any OpenAI-compatible endpoint.
+/// Looked up from a `provider` key in settings rather than guessed from the
+/// model-name prefix, so arbitrary OpenAI-compatible gateways can register
+/// themselves without a matching code change.
+#[async_trait]
+trait LlmClient: Send + Sync {
+    async fn chat(
+        &self,
+        model: &str,
+        history: &[Turn],
+        system_prompt: Option<String>,
+        tools: &[ToolDefinition],
+        emit: Option<StreamEmitter>,
+    ) -> Result<(ChatOutcome, TokenUsage), String>;
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, String>;
+
+    /// Whether this provider can be sent `tools` at all. `true` by default;
+    /// overridden by providers (Perplexity) with no function-calling API.
+    fn supports_tools(&self) -> bool {
+        true
+    }
+}
+
+fn anthropic_tools(tools: &[ToolDefinition]) -> serde_json::Value {
+    serde_json::json!(tools
+        .iter()
+        .map(|t| serde_json::json!({
+            "name": t.name,
+            "description": t.description,
+            "input_schema": t.parameters,
+        }))
+        .collect::<Vec<_>>())
+}
+
+/// Maps history into Anthropic's message array, batching consecutive
+/// `ToolResult` turns into a single `user` message with multiple
+/// `tool_result` blocks (Anthropic rejects back-to-back `user` messages).
+fn anthropic_messages(history: &[Turn]) -> Vec<serde_json::Value> {
+    let mut messages = Vec::new();
+    let mut pending_results: Vec<serde_json::Value> = Vec::new();
+
+    for turn in history {
+        if let Turn::ToolResult { call_id, content, .. } = turn {
+            pending_results.push(serde_json::json!({
+                "type": "tool_result",
+                "tool_use_id": call_id,
+                "content": content,
+            }));
+            continue;
+        }
+
+        if !pending_results.is_empty() {
+            messages.push(serde_json::json!({ "role": "user", "content": std::mem::take(&mut pending_results) }));
+        }
+
+        match turn {
+            Turn::User(text) => messages.push(serde_json::json!({
+                "role": "user",
+                "content": [{ "type": "text", "text": text }]
+            })),
+            Turn::Assistant(text) => messages.push(serde_json::json!({
+                "role": "assistant",
+                "content": [{ "type": "text", "text": text }]
+            })),
+            Turn::ToolCalls(calls) => {
+                let blocks: Vec<_> = calls
+                    .iter()
+                    .map(|c| serde_json::json!({
+                        "type": "tool_use",
+                        "id": c.id,
+                        "name": c.name,
+                        "input": c.arguments,
+                    }))
+                    .collect();
+                messages.push(serde_json::json!({ "role": "assistant", "content": blocks }));
+            }
+            Turn::ToolResult { .. } => unreachable!("handled above"),
         }
     }
 
-    if models.is_empty() {
-        models.extend(vec![
-            ModelInfo { id: "claude-sonnet-4-20250514".to_string(), name: "Claude Sonnet 4 (请先配置API Key)".to_string(), provider: "Anthropic".to_string() },
-            ModelInfo { id: "gpt-4o".to_string(), name: "GPT-4o (请先配置API Key)".to_string(), provider: "OpenAI".to_string() },
-        ]);
+    if !pending_results.is_empty() {
+        messages.push(serde_json::json!({ "role": "user", "content": pending_results }));
     }
 
-    Ok(models)
+    messages
 }
 
-async fn fetch_anthropic_models(client: &Client, api_key: &str) -> Result<Vec<ModelInfo>, String> {
-    let response = client
-        .get("https://api.anthropic.com/v1/models")
-        .header("x-api-key", api_key)
-        .header("anthropic-version", "2023-06-01")
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+struct AnthropicClient {
+    api_key: String,
+}
+
+#[async_trait]
+impl LlmClient for AnthropicClient {
+    async fn chat(
+        &self,
+        model: &str,
+        history: &[Turn],
+        system_prompt: Option<String>,
+        tools: &[ToolDefinition],
+        emit: Option<StreamEmitter>,
+    ) -> Result<(ChatOutcome, TokenUsage), String> {
+        let client = Client::new();
+
+        let mut request = serde_json::json!({
+            "model": model,
+            "messages": anthropic_messages(history),
+            "max_tokens": 4096,
+            "stream": true,
+        });
+        if let Some(system) = system_prompt {
+            request["system"] = serde_json::Value::String(system);
+        }
+        if !tools.is_empty() {
+            request["tools"] = anthropic_tools(tools);
+        }
 
-    if !response.status().is_success() {
-        return Err(format!("HTTP error: {}", response.status()));
+        let response = client
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("Anthropic API error ({}): {}", status, text));
+        }
+
+        let mut text = String::new();
+        let mut tool_blocks: BTreeMap<i64, (String, String, String)> = BTreeMap::new();
+        let mut usage = TokenUsage::default();
+
+        read_sse_stream(response, |data| {
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(data) else {
+                return;
+            };
+            let index = json.get("index").and_then(|i| i.as_i64()).unwrap_or(0);
+            match json.get("type").and_then(|t| t.as_str()) {
+                Some("message_start") => {
+                    if let Some(input_tokens) = json
+                        .get("message")
+                        .and_then(|m| m.get("usage"))
+                        .and_then(|u| u.get("input_tokens"))
+                        .and_then(|v| v.as_u64())
+                    {
+                        usage.input = input_tokens as u32;
+                    }
+                }
+                Some("message_delta") => {
+                    if let Some(output_tokens) = json.get("usage").and_then(|u| u.get("output_tokens")).and_then(|v| v.as_u64()) {
+                        usage.output = output_tokens as u32;
+                    }
+                }
+                Some("content_block_start") => {
+                    if let Some(block) = json.get("content_block") {
+                        if block.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
+                            let id = block.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                            let name = block.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                            tool_blocks.insert(index, (id, name, String::new()));
+                        }
+                    }
+                }
+                Some("content_block_delta") => {
+                    let Some(delta) = json.get("delta") else { return };
+                    match delta.get("type").and_then(|t| t.as_str()) {
+                        Some("text_delta") => {
+                            if let Some(chunk) = delta.get("text").and_then(|t| t.as_str()) {
+                                text.push_str(chunk);
+                                if let Some(emitter) = &emit {
+                                    emitter.emit(chunk);
+                                }
+                            }
+                        }
+                        Some("input_json_delta") => {
+                            if let Some(chunk) = delta.get("partial_json").and_then(|t| t.as_str()) {
+                                if let Some(block) = tool_blocks.get_mut(&index) {
+                                    block.2.push_str(chunk);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        })
+        .await?;
+
+        if !tool_blocks.is_empty() {
+            let calls = tool_blocks
+                .into_values()
+                .map(|(id, name, buffer)| ToolCall {
+                    id,
+                    name,
+                    arguments: serde_json::from_str(&buffer).unwrap_or_else(|_| serde_json::json!({})),
+                })
+                .collect();
+            return Ok((ChatOutcome::ToolCalls(calls), usage));
+        }
+
+        if text.is_empty() {
+            return Err("Empty response from Anthropic".to_string());
+        }
+
+        Ok((ChatOutcome::Text(text), usage))
     }
 
-    let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
-    
-    let mut models = Vec::new();
-    if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
-        for model in data {
-            if let (Some(id), Some(display_name)) = (
-                model.get("id").and_then(|v| v.as_str()),
-                model.get("display_name").and_then(|v| v.as_str())
-            ) {
-                if !id.contains("claude-") && !id.contains("sonnet") && !id.contains("haiku") && !id.contains("opus") {
-                    continue;
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let client = Client::new();
+        let response = client
+            .get("https://api.anthropic.com/v1/models")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP error: {}", response.status()));
+        }
+
+        let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+
+        let mut models = Vec::new();
+        if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
+            for model in data {
+                if let (Some(id), Some(display_name)) = (
+                    model.get("id").and_then(|v| v.as_str()),
+                    model.get("display_name").and_then(|v| v.as_str()),
+                ) {
+                    if !id.contains("claude-") && !id.contains("sonnet") && !id.contains("haiku") && !id.contains("opus") {
+                        continue;
+                    }
+                    models.push(ModelInfo {
+                        id: id.to_string(),
+                        name: display_name.to_string(),
+                        provider: "Anthropic".to_string(),
+                    });
                 }
-                models.push(ModelInfo {
-                    id: id.to_string(),
-                    name: display_name.to_string(),
-                    provider: "Anthropic".to_string(),
-                });
             }
         }
+
+        if models.is_empty() {
+            return Err("Failed to fetch Anthropic models - please check your API key".to_string());
+        }
+
+        Ok(models)
     }
+}
 
-    if models.is_empty() {
-        return Err("Failed to fetch Anthropic models - please check your API key".to_string());
+fn openai_tools(tools: &[ToolDefinition]) -> serde_json::Value {
+    serde_json::json!(tools
+        .iter()
+        .map(|t| serde_json::json!({
+            "type": "function",
+            "function": {
+                "name": t.name,
+                "description": t.description,
+                "parameters": t.parameters,
+            }
+        }))
+        .collect::<Vec<_>>())
+}
+
+fn openai_messages(history: &[Turn], system_prompt: &Option<String>) -> Vec<serde_json::Value> {
+    let mut messages = Vec::new();
+    if let Some(system) = system_prompt {
+        messages.push(serde_json::json!({ "role": "system", "content": system }));
     }
 
-    Ok(models)
+    for turn in history {
+        match turn {
+            Turn::User(text) => messages.push(serde_json::json!({ "role": "user", "content": text })),
+            Turn::Assistant(text) => messages.push(serde_json::json!({ "role": "assistant", "content": text })),
+            Turn::ToolCalls(calls) => {
+                let tool_calls: Vec<_> = calls
+                    .iter()
+                    .map(|c| serde_json::json!({
+                        "id": c.id,
+                        "type": "function",
+                        "function": { "name": c.name, "arguments": c.arguments.to_string() }
+                    }))
+                    .collect();
+                messages.push(serde_json::json!({
+                    "role": "assistant",
+                    "content": serde_json::Value::Null,
+                    "tool_calls": tool_calls,
+                }));
+            }
+            Turn::ToolResult { call_id, content, .. } => {
+                messages.push(serde_json::json!({
+                    "role": "tool",
+                    "tool_call_id": call_id,
+                    "content": content,
+                }));
+            }
+        }
+    }
+
+    messages
+}
+
+/// Any provider that speaks the OpenAI `/chat/completions` + `/models`
+/// shape: OpenAI and xAI out of the box, plus arbitrary custom endpoints
+/// (Ollama, LM Studio, OpenRouter, self-hosted gateways) configured by the
+/// user with their own `base_url`/`api_key`.
+struct OpenAiCompatibleClient {
+    base_url: String,
+    api_key: String,
+    label: String,
 }
 
-async fn fetch_openai_models(client: &Client, api_key: &str) -> Result<Vec<ModelInfo>, String> {
-    let response = client
-        .get("https://api.openai.com/v1/models")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+#[async_trait]
+impl LlmClient for OpenAiCompatibleClient {
+    async fn chat(
+        &self,
+        model: &str,
+        history: &[Turn],
+        system_prompt: Option<String>,
+        tools: &[ToolDefinition],
+        emit: Option<StreamEmitter>,
+    ) -> Result<(ChatOutcome, TokenUsage), String> {
+        let client = Client::new();
+
+        let mut request = serde_json::json!({
+            "model": model,
+            "messages": openai_messages(history, &system_prompt),
+            "max_tokens": 4096,
+            "stream": true,
+            "stream_options": { "include_usage": true },
+        });
+        if !tools.is_empty() {
+            request["tools"] = openai_tools(tools);
+        }
+
+        let response = client
+            .post(format!("{}/chat/completions", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("{} API error ({}): {}", self.label, status, text));
+        }
+
+        let mut text = String::new();
+        let mut tool_calls: BTreeMap<i64, (String, String, String)> = BTreeMap::new();
+        let mut usage = TokenUsage::default();
+
+        read_sse_stream(response, |data| {
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(data) else {
+                return;
+            };
+
+            if let Some(u) = json.get("usage") {
+                if let Some(prompt_tokens) = u.get("prompt_tokens").and_then(|v| v.as_u64()) {
+                    usage.input = prompt_tokens as u32;
+                }
+                if let Some(completion_tokens) = u.get("completion_tokens").and_then(|v| v.as_u64()) {
+                    usage.output = completion_tokens as u32;
+                }
+            }
+
+            let Some(delta) = json.get("choices").and_then(|c| c.get(0)).and_then(|c| c.get("delta")) else {
+                return;
+            };
+
+            if let Some(chunk) = delta.get("content").and_then(|t| t.as_str()) {
+                text.push_str(chunk);
+                if let Some(emitter) = &emit {
+                    emitter.emit(chunk);
+                }
+            }
+
+            if let Some(calls) = delta.get("tool_calls").and_then(|t| t.as_array()) {
+                for call in calls {
+                    let index = call.get("index").and_then(|i| i.as_i64()).unwrap_or(0);
+                    let entry = tool_calls.entry(index).or_insert_with(|| (String::new(), String::new(), String::new()));
+                    if let Some(id) = call.get("id").and_then(|v| v.as_str()) {
+                        entry.0 = id.to_string();
+                    }
+                    if let Some(function) = call.get("function") {
+                        if let Some(name) = function.get("name").and_then(|v| v.as_str()) {
+                            entry.1.push_str(name);
+                        }
+                        if let Some(args) = function.get("arguments").and_then(|v| v.as_str()) {
+                            entry.2.push_str(args);
+                        }
+                    }
+                }
+            }
+        })
+        .await?;
+
+        if !tool_calls.is_empty() {
+            let calls = tool_calls
+                .into_values()
+                .map(|(id, name, buffer)| ToolCall {
+                    id,
+                    name,
+                    arguments: serde_json::from_str(&buffer).unwrap_or_else(|_| serde_json::json!({})),
+                })
+                .collect();
+            return Ok((ChatOutcome::ToolCalls(calls), usage));
+        }
+
+        if text.is_empty() {
+            return Err(format!("Empty response from {}", self.label));
+        }
 
-    if !response.status().is_success() {
-        return Err(format!("HTTP error: {}", response.status()));
+        Ok((ChatOutcome::Text(text), usage))
     }
 
-    let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
-    
-    let mut models = Vec::new();
-    if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
-        for model in data {
-            if let Some(id) = model.get("id").and_then(|v| v.as_str()) {
-                let filter_models = ["gpt-4o", "gpt-4", "gpt-3.5", "o1", "o3", "o4"];
-                if !filter_models.iter().any(|f| id.contains(f)) {
-                    continue;
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let client = Client::new();
+        let response = client
+            .get(format!("{}/models", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP error: {}", response.status()));
+        }
+
+        let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+
+        let mut models = Vec::new();
+        if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
+            for model in data {
+                if let Some(id) = model.get("id").and_then(|v| v.as_str()) {
+                    let name = model.get("human_name").and_then(|v| v.as_str()).unwrap_or(id);
+                    models.push(ModelInfo {
+                        id: id.to_string(),
+                        name: name.to_string(),
+                        provider: self.label.clone(),
+                    });
                 }
-                let name = model.get("human_name").and_then(|v| v.as_str()).unwrap_or(id);
-                models.push(ModelInfo {
-                    id: id.to_string(),
-                    name: name.to_string(),
-                    provider: "OpenAI".to_string(),
-                });
             }
         }
+
+        if models.is_empty() {
+            return Err(format!("Failed to fetch {} models - please check your API key", self.label));
+        }
+
+        Ok(models)
     }
+}
 
-    models.sort_by(|a, b| {
-        let priority = |id: &str| {
-            if id.contains("4o") { 0 }
-            else if id.contains("o1") { 1 }
-            else if id.contains("o3") { 2 }
-            else if id.contains("4") { 3 }
-            else { 4 }
-        };
-        priority(&a.id).cmp(&priority(&b.id))
-    });
+fn google_tools(tools: &[ToolDefinition]) -> serde_json::Value {
+    serde_json::json!([{
+        "functionDeclarations": tools.iter().map(|t| serde_json::json!({
+            "name": t.name,
+            "description": t.description,
+            "parameters": t.parameters,
+        })).collect::<Vec<_>>()
+    }])
+}
 
-    Ok(models)
+fn google_contents(history: &[Turn]) -> Vec<serde_json::Value> {
+    history
+        .iter()
+        .map(|turn| match turn {
+            Turn::User(text) => serde_json::json!({ "role": "user", "parts": [{ "text": text }] }),
+            Turn::Assistant(text) => serde_json::json!({ "role": "model", "parts": [{ "text": text }] }),
+            Turn::ToolCalls(calls) => serde_json::json!({
+                "role": "model",
+                "parts": calls.iter().map(|c| serde_json::json!({
+                    "functionCall": { "name": c.name, "args": c.arguments }
+                })).collect::<Vec<_>>()
+            }),
+            Turn::ToolResult { name, content, .. } => serde_json::json!({
+                "role": "function",
+                "parts": [{
+                    "functionResponse": { "name": name, "response": { "content": content } }
+                }]
+            }),
+        })
+        .collect()
 }
 
-async fn fetch_google_models(client: &Client, api_key: &str) -> Result<Vec<ModelInfo>, String> {
-    let response = client
-        .get(&format!("https://generativelanguage.googleapis.com/v1/models?key={}", api_key))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+struct GoogleClient {
+    api_key: String,
+}
 
-    if !response.status().is_success() {
-        return Err(format!("HTTP error: {}", response.status()));
-    }
+#[async_trait]
+impl LlmClient for GoogleClient {
+    async fn chat(
+        &self,
+        model: &str,
+        history: &[Turn],
+        system_prompt: Option<String>,
+        tools: &[ToolDefinition],
+        emit: Option<StreamEmitter>,
+    ) -> Result<(ChatOutcome, TokenUsage), String> {
+        let client = Client::new();
+
+        let url = format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:streamGenerateContent?alt=sse&key={}",
+            model, self.api_key
+        );
+
+        let mut request = serde_json::json!({
+            "contents": google_contents(history),
+            "generationConfig": {
+                "maxOutputTokens": 4096,
+                "temperature": 0.9
+            }
+        });
 
-    let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
-    
-    let mut models = Vec::new();
-    if let Some(data) = json.get("models").and_then(|d| d.as_array()) {
-        for model in data {
-            if let Some(name) = model.get("name").and_then(|v| v.as_str()) {
-                if !name.contains("gemini") {
-                    continue;
+        if let Some(system) = system_prompt {
+            request["systemInstruction"] = serde_json::json!({
+                "parts": [{ "text": system }]
+            });
+        }
+        if !tools.is_empty() {
+            request["tools"] = google_tools(tools);
+        }
+
+        let response = client
+            .post(&url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("Google API error ({}): {}", status, text));
+        }
+
+        let mut text = String::new();
+        let mut tool_calls: Vec<ToolCall> = Vec::new();
+        let mut call_counter = 0i64;
+        let mut usage = TokenUsage::default();
+
+        read_sse_stream(response, |data| {
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(data) else {
+                return;
+            };
+
+            if let Some(u) = json.get("usageMetadata") {
+                if let Some(prompt_tokens) = u.get("promptTokenCount").and_then(|v| v.as_u64()) {
+                    usage.input = prompt_tokens as u32;
+                }
+                if let Some(output_tokens) = u.get("candidatesTokenCount").and_then(|v| v.as_u64()) {
+                    usage.output = output_tokens as u32;
+                }
+            }
+
+            let Some(parts) = json
+                .get("candidates")
+                .and_then(|c| c.get(0))
+                .and_then(|c| c.get("content"))
+                .and_then(|c| c.get("parts"))
+                .and_then(|p| p.as_array())
+            else {
+                return;
+            };
+
+            for part in parts {
+                if let Some(chunk) = part.get("text").and_then(|t| t.as_str()) {
+                    text.push_str(chunk);
+                    if let Some(emitter) = &emit {
+                        emitter.emit(chunk);
+                    }
+                }
+                if let Some(function_call) = part.get("functionCall") {
+                    let name = function_call.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                    let arguments = function_call.get("args").cloned().unwrap_or_else(|| serde_json::json!({}));
+                    call_counter += 1;
+                    tool_calls.push(ToolCall {
+                        id: format!("google-call-{}", call_counter),
+                        name,
+                        arguments,
+                    });
                 }
-                let model_id = name.replace("models/", "");
-                let display_name = model_id.replace("-", " ");
-                models.push(ModelInfo {
-                    id: model_id,
-                    name: display_name,
-                    provider: "Google".to_string(),
-                });
             }
+        })
+        .await?;
+
+        if !tool_calls.is_empty() {
+            return Ok((ChatOutcome::ToolCalls(tool_calls), usage));
+        }
+
+        if text.is_empty() {
+            return Err("Empty response from Google".to_string());
         }
+
+        Ok((ChatOutcome::Text(text), usage))
     }
 
-    if models.is_empty() {
-        return Err("Failed to fetch Google models - please check your API key".to_string());
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let client = Client::new();
+        let response = client
+            .get(format!("https://generativelanguage.googleapis.com/v1/models?key={}", self.api_key))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP error: {}", response.status()));
+        }
+
+        let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+
+        let mut models = Vec::new();
+        if let Some(data) = json.get("models").and_then(|d| d.as_array()) {
+            for model in data {
+                if let Some(name) = model.get("name").and_then(|v| v.as_str()) {
+                    if !name.contains("gemini") {
+                        continue;
+                    }
+                    let model_id = name.replace("models/", "");
+                    let display_name = model_id.replace("-", " ");
+                    models.push(ModelInfo {
+                        id: model_id,
+                        name: display_name,
+                        provider: "Google".to_string(),
+                    });
+                }
+            }
+        }
+
+        if models.is_empty() {
+            return Err("Failed to fetch Google models - please check your API key".to_string());
+        }
+
+        Ok(models)
     }
+}
 
-    Ok(models)
+/// Perplexity has no function-calling API, so `tools` is accepted but
+/// ignored — it always returns plain text.
+struct PerplexityClient {
+    api_key: String,
 }
 
-async fn fetch_xai_models(client: &Client, api_key: &str) -> Result<Vec<ModelInfo>, String> {
-    let response = client
-        .get("https://api.x.ai/v1/models")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+#[async_trait]
+impl LlmClient for PerplexityClient {
+    async fn chat(
+        &self,
+        model: &str,
+        history: &[Turn],
+        system_prompt: Option<String>,
+        _tools: &[ToolDefinition],
+        emit: Option<StreamEmitter>,
+    ) -> Result<(ChatOutcome, TokenUsage), String> {
+        let model_name = model.trim_start_matches("perplexity-");
+
+        let client = Client::new();
+        let request = serde_json::json!({
+            "model": format!("llama-3.1-sonar-{}-128k-online", model_name),
+            "messages": openai_messages(history, &system_prompt),
+            "max_tokens": 4096,
+            "stream": true,
+        });
+
+        let response = client
+            .post("https://api.perplexity.ai/chat/completions")
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("Perplexity API error ({}): {}", status, text));
+        }
+
+        let mut text = String::new();
+        let mut usage = TokenUsage::default();
+        read_sse_stream(response, |data| {
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(data) else {
+                return;
+            };
+
+            if let Some(u) = json.get("usage") {
+                if let Some(prompt_tokens) = u.get("prompt_tokens").and_then(|v| v.as_u64()) {
+                    usage.input = prompt_tokens as u32;
+                }
+                if let Some(completion_tokens) = u.get("completion_tokens").and_then(|v| v.as_u64()) {
+                    usage.output = completion_tokens as u32;
+                }
+            }
+
+            if let Some(delta) = json
+                .get("choices")
+                .and_then(|c| c.get(0))
+                .and_then(|c| c.get("delta"))
+                .and_then(|d| d.get("content"))
+                .and_then(|t| t.as_str())
+            {
+                text.push_str(delta);
+                if let Some(emitter) = &emit {
+                    emitter.emit(delta);
+                }
+            }
+        })
+        .await?;
+
+        if text.is_empty() {
+            return Err("Empty response from Perplexity".to_string());
+        }
 
-    if !response.status().is_success() {
-        return Err(format!("HTTP error: {}", response.status()));
+        Ok((ChatOutcome::Text(text), usage))
     }
 
-    let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
-    
-    let mut models = Vec::new();
-    if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
-        for model in data {
-            if let Some(id) = model.get("id").and_then(|v| v.as_str()) {
-                let name = model.get("human_name").and_then(|v| v.as_str()).unwrap_or(id);
-                models.push(ModelInfo {
-                    id: id.to_string(),
-                    name: name.to_string(),
-                    provider: "xAI".to_string(),
-                });
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, String> {
+        let client = Client::new();
+        let response = client
+            .get("https://api.perplexity.ai/models")
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP error: {}", response.status()));
+        }
+
+        let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+
+        let mut models = Vec::new();
+        if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
+            for model in data {
+                if let (Some(id), Some(name)) = (
+                    model.get("id").and_then(|v| v.as_str()),
+                    model.get("name").and_then(|v| v.as_str()),
+                ) {
+                    models.push(ModelInfo {
+                        id: id.to_string(),
+                        name: name.to_string(),
+                        provider: "Perplexity".to_string(),
+                    });
+                }
             }
         }
+
+        if models.is_empty() {
+            return Err("Failed to fetch Perplexity models - please check your API key".to_string());
+        }
+
+        Ok(models)
     }
 
-    if models.is_empty() {
-        return Err("Failed to fetch xAI models - please check your API key".to_string());
+    fn supports_tools(&self) -> bool {
+        false
     }
+}
 
-    Ok(models)
+/// Builds the client for `provider`, checking the built-in registry first
+/// and falling back to a matching entry in `settings.custom_providers`.
+/// Returns `None` if the provider is unknown, or is a built-in one with no
+/// API key configured; custom providers are built even with an empty key.
+fn build_client(provider: &str, settings: &Settings) -> Option<Box<dyn LlmClient>> {
+    match provider {
+        "anthropic" if !settings.anthropic_api_key.is_empty() => Some(Box::new(AnthropicClient {
+            api_key: settings.anthropic_api_key.clone(),
+        })),
+        "openai" if !settings.openai_api_key.is_empty() => Some(Box::new(OpenAiCompatibleClient {
+            base_url: "https://api.openai.com/v1".to_string(),
+            api_key: settings.openai_api_key.clone(),
+            label: "OpenAI".to_string(),
+        })),
+        "xai" if !settings.xai_api_key.is_empty() => Some(Box::new(OpenAiCompatibleClient {
+            base_url: "https://api.x.ai/v1".to_string(),
+            api_key: settings.xai_api_key.clone(),
+            label: "xAI".to_string(),
+        })),
+        "google" if !settings.google_api_key.is_empty() => Some(Box::new(GoogleClient {
+            api_key: settings.google_api_key.clone(),
+        })),
+        "perplexity" if !settings.perplexity_api_key.is_empty() => Some(Box::new(PerplexityClient {
+            api_key: settings.perplexity_api_key.clone(),
+        })),
+        _ => settings
+            .custom_providers
+            .iter()
+            .find(|p| p.name == provider)
+            .map(|p| Box::new(OpenAiCompatibleClient {
+                base_url: p.base_url.trim_end_matches('/').to_string(),
+                api_key: p.api_key.clone(),
+                label: p.name.clone(),
+            }) as Box<dyn LlmClient>),
+    }
 }
 
-async fn fetch_perplexity_models(client: &Client, api_key: &str) -> Result<Vec<ModelInfo>, String> {
-    let response = client
-        .get("https://api.perplexity.ai/models")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+pub async fn get_models(state: State<'_, AppState>) -> Result<Vec<ModelInfo>, String> {
+    let settings = {
+        let settings = state.settings.lock().map_err(|e| e.to_string())?;
+        settings.clone()
+    };
 
-    if !response.status().is_success() {
-        return Err(format!("HTTP error: {}", response.status()));
-    }
+    let known_providers = ["anthropic", "openai", "google", "xai", "perplexity"];
+    let custom_providers: Vec<String> = settings.custom_providers.iter().map(|p| p.name.clone()).collect();
 
-    let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
-    
     let mut models = Vec::new();
-    if let Some(data) = json.get("data").and_then(|d| d.as_array()) {
-        for model in data {
-            if let (Some(id), Some(name)) = (
-                model.get("id").and_then(|v| v.as_str()),
-                model.get("name").and_then(|v| v.as_str())
-            ) {
-                models.push(ModelInfo {
-                    id: id.to_string(),
-                    name: name.to_string(),
-                    provider: "Perplexity".to_string(),
-                });
-            }
+    for provider in known_providers.iter().map(|s| s.to_string()).chain(custom_providers) {
+        let Some(client) = build_client(&provider, &settings) else {
+            continue;
+        };
+        match client.list_models().await {
+            Ok(m) => models.extend(m),
+            Err(e) => println!("Failed to fetch {} models: {}", provider, e),
         }
     }
 
     if models.is_empty() {
-        return Err("Failed to fetch Perplexity models - please check your API key".to_string());
+        models.extend(vec![
+            ModelInfo { id: "claude-sonnet-4-20250514".to_string(), name: "Claude Sonnet 4 (请先配置API Key)".to_string(), provider: "Anthropic".to_string() },
+            ModelInfo { id: "gpt-4o".to_string(), name: "GPT-4o (请先配置API Key)".to_string(), provider: "OpenAI".to_string() },
+        ]);
     }
 
     Ok(models)
@@ -359,50 +1321,152 @@ async fn fetch_perplexity_models(client: &Client, api_key: &str) -> Result<Vec<M
 #[tauri::command]
 pub async fn chat(
     state: State<'_, AppState>,
+    app_handle: AppHandle,
+    message: String,
+    model: Option<String>,
+    provider: Option<String>,
+    system_prompt: Option<String>,
+) -> Result<String, String> {
+    chat_internal(state, app_handle, message, model, provider, system_prompt, None).await
+}
+
+/// Same request as `chat`, but emits each incremental chunk as `event_name`
+/// (`{id, delta}`) while the response streams in, plus a `{event_name}-tool-call`
+/// event per tool invocation, in addition to returning the fully collected
+/// text once the stream (and any tool round trips) complete.
+#[tauri::command]
+pub async fn chat_stream(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    message: String,
+    model: Option<String>,
+    provider: Option<String>,
+    system_prompt: Option<String>,
+    event_name: String,
+) -> Result<String, String> {
+    let id = chrono::Utc::now().timestamp_millis().to_string();
+    chat_internal(state, app_handle, message, model, provider, system_prompt, Some((event_name, id))).await
+}
+
+/// Hands any hook-triggered memory candidates in the current message history
+/// off to `state.extraction_queue`. A no-op when no `CompletionProvider` is
+/// configured, since there'd be nothing to run the extraction prompt against.
+fn enqueue_memory_extraction(state: &State<'_, AppState>) -> Result<(), String> {
+    let provider = {
+        let provider = state.completion_provider.lock().map_err(|e| e.to_string())?;
+        provider.clone()
+    };
+    let Some(provider) = provider else {
+        return Ok(());
+    };
+
+    let snapshot = {
+        let messages = state.messages.lock().map_err(|e| e.to_string())?;
+        messages.clone()
+    };
+
+    let hooks = Arc::new(crate::hooks::HookSystem::new());
+    for (window, kind) in hooks.candidate_windows(&snapshot) {
+        state.extraction_queue.submit(window, kind, hooks.clone(), provider.clone());
+    }
+
+    Ok(())
+}
+
+/// If `user_message` contains constraint language ("must", "不能", ...),
+/// hands the just-produced `response` off to `state.validation_queue` for a
+/// background generate-critique-revise pass. A no-op when no constraints
+/// were found (nothing to judge) or no `CompletionProvider` is configured.
+fn enqueue_response_validation(
+    state: &State<'_, AppState>,
+    user_message: &str,
+    response: &str,
+) -> Result<(), String> {
+    let provider = {
+        let provider = state.completion_provider.lock().map_err(|e| e.to_string())?;
+        provider.clone()
+    };
+    let Some(provider) = provider else {
+        return Ok(());
+    };
+
+    let framework = Arc::new(crate::algorithm::AlgorithmFramework::new());
+    let constraints = framework.extract_constraints(user_message);
+    if constraints.is_empty() {
+        return Ok(());
+    }
+
+    let context = crate::algorithm::AlgorithmContext {
+        user_requirements: user_message.to_string(),
+        constraints,
+        plan: String::new(),
+        validation_result: None,
+        reflection: None,
+    };
+    state.validation_queue.submit(context, response.to_string(), framework, provider);
+
+    Ok(())
+}
+
+async fn chat_internal(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
     message: String,
     model: Option<String>,
+    provider: Option<String>,
     system_prompt: Option<String>,
+    stream_target: Option<(String, String)>,
 ) -> Result<String, String> {
-    let (default_model, api_keys) = {
+    let (default_model, default_provider, settings) = {
         let settings = state.settings.lock().map_err(|e| e.to_string())?;
-        (settings.default_model.clone(), SettingsApiKeys {
-            anthropic: settings.anthropic_api_key.clone(),
-            openai: settings.openai_api_key.clone(),
-            google: settings.google_api_key.clone(),
-            xai: settings.xai_api_key.clone(),
-            perplexity: settings.perplexity_api_key.clone(),
-        })
+        (settings.default_model.clone(), settings.default_provider.clone(), settings.clone())
     };
-    
-    let model = model.unwrap_or(default_model);
-    let provider = get_model_provider(&model);
 
-    let context = build_context(&state).map_err(|e| e.to_string())?;
-    let full_message = if context.is_empty() {
-        message.clone()
-    } else {
-        format!("{}\n\nUser: {}", context, message)
+    let model = model.unwrap_or(default_model);
+    let provider = provider.unwrap_or(default_provider);
+
+    let (mut history, memory_context) = build_history(&state, &model, &message, system_prompt.as_deref(), &settings.openai_api_key).await?;
+    let effective_system = match (system_prompt, memory_context) {
+        (Some(s), Some(c)) => Some(format!("{}\n\n{}", s, c)),
+        (Some(s), None) => Some(s),
+        (None, Some(c)) => Some(c),
+        (None, None) => None,
     };
 
     let user_message = ChatMessage {
         role: "user".to_string(),
         content: message.clone(),
         timestamp: chrono::Utc::now().timestamp(),
+        conversation_id: None,
+        usage: None,
+        token_count: Some(tokenizer::count_tokens(&model, &message)),
+    };
+
+    let emit = stream_target.map(|(event_name, id)| StreamEmitter {
+        app_handle: app_handle.clone(),
+        event_name,
+        id,
+    });
+
+    let tools = tool_definitions();
+    let result = match build_client(&provider, &settings) {
+        Some(client) => run_tool_loop(&app_handle, client.as_ref(), &model, &mut history, effective_system, &tools, emit).await,
+        None => Err(format!("Unknown or unconfigured provider: {}", provider)),
     };
 
-    let response = match provider {
-        "anthropic" => chat_anthropic(&api_keys, &model, &full_message, system_prompt).await,
-        "openai" => chat_openai(&api_keys, &model, &full_message, system_prompt).await,
-        "google" => chat_google(&api_keys, &model, &full_message, system_prompt).await,
-        "xai" => chat_xai(&api_keys, &model, &full_message, system_prompt).await,
-        "perplexity" => chat_perplexity(&api_keys, &model, &full_message, system_prompt).await,
-        _ => chat_anthropic(&api_keys, &model, &full_message, system_prompt).await,
+    let (response, usage) = match result {
+        Ok((text, usage)) => (Ok(text), Some(usage)),
+        Err(e) => (Err(e), None),
     };
 
+    let assistant_content = response.clone().unwrap_or_else(|e| e.clone());
     let assistant_message = ChatMessage {
         role: "assistant".to_string(),
-        content: response.clone().unwrap_or_else(|e| e.clone()),
+        token_count: Some(tokenizer::count_tokens(&model, &assistant_content)),
+        content: assistant_content,
         timestamp: chrono::Utc::now().timestamp(),
+        conversation_id: None,
+        usage,
     };
 
     {
@@ -413,55 +1477,71 @@ pub async fn chat(
         }
     }
 
+    if let Ok(response_text) = &response {
+        enqueue_memory_extraction(&state)?;
+        enqueue_response_validation(&state, &message, response_text)?;
+    }
+
+    if let Some(usage) = usage {
+        let session_id = crate::session::get_current_session().map(|s| s.id).unwrap_or_else(|_| "default".to_string());
+        let mut stats = state.usage.lock().map_err(|e| e.to_string())?;
+        stats.per_model.entry(model).or_default().accumulate(&usage);
+        stats.per_session.entry(session_id).or_default().accumulate(&usage);
+    }
+
     response
 }
 
-#[derive(Clone)]
-struct SettingsApiKeys {
-    anthropic: String,
-    openai: String,
-    google: String,
-    xai: String,
-    perplexity: String,
+/// Per-model and per-session totals for the current estimated usage, plus
+/// the running cost estimate from `PRICE_TABLE_PER_MILLION`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelUsageStat {
+    pub model: String,
+    pub usage: TokenUsage,
+    pub estimated_cost_usd: f64,
 }
 
-fn build_context(state: &AppState) -> Result<String, String> {
-    let messages = state.messages.lock().map_err(|e| e.to_string())?;
-    let memories = state.memories.lock().map_err(|e| e.to_string())?;
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionUsageStat {
+    pub session_id: String,
+    pub usage: TokenUsage,
+}
 
-    let mut context = String::new();
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageStatsResponse {
+    pub total_usage: TokenUsage,
+    pub total_cost_usd: f64,
+    pub per_model: Vec<ModelUsageStat>,
+    pub per_session: Vec<SessionUsageStat>,
+}
 
-    if let Some(last_user_msg) = messages.iter().rev().find(|m| m.role == "user") {
-        let query = extract_keywords(&last_user_msg.content);
-        if !query.is_empty() {
-            let relevant: Vec<_> = memories
-                .iter()
-                .filter(|m| {
-                    m.title.to_lowercase().contains(&query)
-                        || m.tags.iter().any(|t| t.to_lowercase().contains(&query))
-                        || m.entities.iter().any(|e| e.to_lowercase().contains(&query))
-                })
-                .take(5)
-                .collect();
+#[tauri::command]
+pub fn get_usage_stats(state: State<'_, AppState>) -> Result<UsageStatsResponse, String> {
+    let stats = state.usage.lock().map_err(|e| e.to_string())?;
+
+    let per_model: Vec<ModelUsageStat> = stats
+        .per_model
+        .iter()
+        .map(|(model, usage)| ModelUsageStat {
+            model: model.clone(),
+            usage: *usage,
+            estimated_cost_usd: estimated_cost_usd(model, usage),
+        })
+        .collect();
 
-            if !relevant.is_empty() {
-                context.push_str("## Relevant Memories\n");
-                for memory in &relevant {
-                    context.push_str(&format!("### {}\n{}\n\n", memory.title, memory.content));
-                }
-            }
-        }
-    }
+    let per_session: Vec<SessionUsageStat> = stats
+        .per_session
+        .iter()
+        .map(|(session_id, usage)| SessionUsageStat { session_id: session_id.clone(), usage: *usage })
+        .collect();
 
-    if memories.is_empty() && !messages.is_empty() {
-        context.push_str("## Recent Conversation\n");
-        let recent: Vec<_> = messages.iter().rev().take(10).collect();
-        for msg in recent.iter().rev() {
-            context.push_str(&format!("{}: {}\n", msg.role, msg.content));
-        }
-    }
+    let total_usage = stats.per_model.values().fold(TokenUsage::default(), |mut acc, u| {
+        acc.accumulate(u);
+        acc
+    });
+    let total_cost_usd = per_model.iter().map(|m| m.estimated_cost_usd).sum();
 
-    Ok(context)
+    Ok(UsageStatsResponse { total_usage, total_cost_usd, per_model, per_session })
 }
 
 fn extract_keywords(text: &str) -> String {
@@ -489,264 +1569,3 @@ fn extract_keywords(text: &str) -> String {
 
     words.join(" ")
 }
-
-async fn chat_anthropic(
-    settings: &SettingsApiKeys,
-    model: &str,
-    message: &str,
-    system_prompt: Option<String>,
-) -> Result<String, String> {
-    if settings.anthropic.is_empty() {
-        return Err("Anthropic API key not configured".to_string());
-    }
-
-    let client = Client::new();
-    
-    let request = AnthropicRequest {
-        model: model.to_string(),
-        messages: vec![AnthropicMessage {
-            role: "user".to_string(),
-            content: message.to_string(),
-        }],
-        max_tokens: 4096,
-        system: system_prompt,
-    };
-
-    let response = client
-        .post("https://api.anthropic.com/v1/messages")
-        .header("x-api-key", &settings.anthropic)
-        .header("anthropic-version", "2023-06-01")
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Anthropic API error ({}): {}", status, text));
-    }
-
-    let response: AnthropicResponse = response.json().await.map_err(|e| e.to_string())?;
-
-    response
-        .content
-        .first()
-        .and_then(|c| c.text.clone())
-        .ok_or_else(|| "Empty response from Anthropic".to_string())
-}
-
-async fn chat_openai(
-    settings: &SettingsApiKeys,
-    model: &str,
-    message: &str,
-    system_prompt: Option<String>,
-) -> Result<String, String> {
-    if settings.openai.is_empty() {
-        return Err("OpenAI API key not configured".to_string());
-    }
-
-    let client = Client::new();
-
-    let mut messages = Vec::new();
-    
-    if let Some(system) = system_prompt {
-        messages.push(OpenAIMessage {
-            role: "system".to_string(),
-            content: system,
-        });
-    }
-    
-    messages.push(OpenAIMessage {
-        role: "user".to_string(),
-        content: message.to_string(),
-    });
-
-    let request = OpenAIRequest {
-        model: model.to_string(),
-        messages,
-        max_tokens: Some(4096),
-    };
-
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
-        .header("Authorization", format!("Bearer {}", settings.openai))
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("OpenAI API error ({}): {}", status, text));
-    }
-
-    let response: OpenAIResponse = response.json().await.map_err(|e| e.to_string())?;
-
-    response
-        .choices
-        .first()
-        .map(|c| c.message.content.clone())
-        .ok_or_else(|| "Empty response from OpenAI".to_string())
-}
-
-async fn chat_google(
-    settings: &SettingsApiKeys,
-    model: &str,
-    message: &str,
-    _system_prompt: Option<String>,
-) -> Result<String, String> {
-    if settings.google.is_empty() {
-        return Err("Google API key not configured".to_string());
-    }
-
-    let client = Client::new();
-    
-    let _model_name = model.trim_start_matches("gemini-");
-    let url = format!(
-        "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-        model, settings.google
-    );
-
-    let request = serde_json::json!({
-        "contents": [{
-            "parts": [{
-                "text": message
-            }]
-        }],
-        "generationConfig": {
-            "maxOutputTokens": 4096,
-            "temperature": 0.9
-        }
-    });
-
-    let response = client
-        .post(&url)
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Google API error ({}): {}", status, text));
-    }
-
-    let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
-    
-    json.get("candidates")
-        .and_then(|c| c.as_array())
-        .and_then(|c| c.first())
-        .and_then(|c| c.get("content"))
-        .and_then(|c| c.get("parts"))
-        .and_then(|p| p.as_array())
-        .and_then(|p| p.first())
-        .and_then(|p| p.get("text"))
-        .and_then(|t| t.as_str())
-        .map(|s| s.to_string())
-        .ok_or_else(|| "Empty response from Google".to_string())
-}
-
-async fn chat_xai(
-    settings: &SettingsApiKeys,
-    model: &str,
-    message: &str,
-    system_prompt: Option<String>,
-) -> Result<String, String> {
-    if settings.xai.is_empty() {
-        return Err("xAI API key not configured".to_string());
-    }
-
-    let client = Client::new();
-
-    let mut messages = Vec::new();
-    
-    if let Some(system) = system_prompt {
-        messages.push(OpenAIMessage {
-            role: "system".to_string(),
-            content: system,
-        });
-    }
-    
-    messages.push(OpenAIMessage {
-        role: "user".to_string(),
-        content: message.to_string(),
-    });
-
-    let request = OpenAIRequest {
-        model: model.to_string(),
-        messages,
-        max_tokens: Some(4096),
-    };
-
-    let response = client
-        .post("https://api.x.ai/v1/chat/completions")
-        .header("Authorization", format!("Bearer {}", settings.xai))
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("xAI API error ({}): {}", status, text));
-    }
-
-    let response: OpenAIResponse = response.json().await.map_err(|e| e.to_string())?;
-
-    response
-        .choices
-        .first()
-        .map(|c| c.message.content.clone())
-        .ok_or_else(|| "Empty response from xAI".to_string())
-}
-
-async fn chat_perplexity(
-    settings: &SettingsApiKeys,
-    model: &str,
-    message: &str,
-    _system_prompt: Option<String>,
-) -> Result<String, String> {
-    if settings.perplexity.is_empty() {
-        return Err("Perplexity API key not configured".to_string());
-    }
-
-    let client = Client::new();
-
-    let model_name = model.trim_start_matches("perplexity-");
-    let url = "https://api.perplexity.ai/chat/completions";
-
-    let request = OpenAIRequest {
-        model: format!("llama-3.1-sonar-{}-128k-online", model_name),
-        messages: vec![OpenAIMessage {
-            role: "user".to_string(),
-            content: message.to_string(),
-        }],
-        max_tokens: Some(4096),
-    };
-
-    let response = client
-        .post(url)
-        .header("Authorization", format!("Bearer {}", settings.perplexity))
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        return Err(format!("Perplexity API error ({}): {}", status, text));
-    }
-
-    let response: OpenAIResponse = response.json().await.map_err(|e| e.to_string())?;
-
-    response
-        .choices
-        .first()
-        .map(|c| c.message.content.clone())
-        .ok_or_else(|| "Empty response from Perplexity".to_string())
-}
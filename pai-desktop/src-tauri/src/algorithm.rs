@@ -1,5 +1,9 @@
-use crate::{ChatMessage, MemoryItem, Settings};
+use crate::completion::{CompletionOptions, CompletionProvider};
 use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use threadpool::ThreadPool;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlgorithmContext {
@@ -13,6 +17,10 @@ pub struct AlgorithmContext {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationResult {
     pub passed: bool,
+    /// The judge model's confidence in `passed`, in `[0.0, 1.0]`. A failing
+    /// result below `min_confidence_threshold` triggers a revision round
+    /// instead of being accepted as-is.
+    pub confidence: f32,
     pub issues: Vec<String>,
     pub suggestions: Vec<String>,
 }
@@ -102,54 +110,128 @@ impl AlgorithmFramework {
         }
     }
 
-    pub fn validate_output(&self, output: &str, constraints: &[String], settings: &Settings) -> ValidationResult {
-        let mut issues = Vec::new();
-        let mut suggestions = Vec::new();
-
-        for constraint in constraints {
-            let constraint_lower = constraint.to_lowercase();
-            
-            if constraint_lower.contains("不能") || constraint_lower.contains("不要") 
-                || constraint_lower.contains("must not") || constraint_lower.contains("cannot") 
-                || constraint_lower.contains("don't") {
-                if self.constraint_violated(output, constraint) {
-                    issues.push(format!("Constraint violated: {}", constraint));
-                    suggestions.push(format!("Remove content related to: {}", constraint));
-                }
-            }
-
-            if constraint_lower.contains("仅") || constraint_lower.contains("只能") 
-                || constraint_lower.contains("only") {
-                if !self.constraint_satisfied(output, constraint) {
-                    issues.push(format!("Constraint not satisfied: {}", constraint));
-                }
-            }
+    /// Asks `provider` to judge `output` against `constraints` and returns a
+    /// structured `ValidationResult`. Replaces the old keyword-matching
+    /// heuristic, which flagged any output that merely repeated a word from
+    /// a "must not" constraint.
+    pub fn validate_output(&self, output: &str, constraints: &[String], provider: &dyn CompletionProvider) -> ValidationResult {
+        if constraints.is_empty() {
+            return ValidationResult {
+                passed: true,
+                confidence: 1.0,
+                issues: Vec::new(),
+                suggestions: Vec::new(),
+            };
         }
 
-        if output.len() < 10 {
-            issues.push("Output is too short".to_string());
+        let prompt = format!(
+            r#"You are judging whether an AI response satisfies a list of constraints.
+
+Constraints:
+{}
+
+Response to judge:
+{}
+
+Respond with ONLY a JSON object of this shape, no other text:
+{{"passed": bool, "confidence": number between 0 and 1, "issues": [string, ...], "suggestions": [string, ...]}}
+
+"issues" should name each violated or unsatisfied constraint; "suggestions" should say how to fix it. If every constraint is met, return passed: true with empty issues and suggestions."#,
+            constraints.join("\n"),
+            output
+        );
+
+        match provider.complete(&prompt, &CompletionOptions { max_tokens: 512 }) {
+            Ok(response) => self.parse_validation_response(&response),
+            Err(e) => ValidationResult {
+                passed: false,
+                confidence: 0.0,
+                issues: vec![format!("Validation call failed: {}", e)],
+                suggestions: Vec::new(),
+            },
         }
+    }
 
-        ValidationResult {
-            passed: issues.is_empty(),
-            issues,
-            suggestions,
+    fn parse_validation_response(&self, response: &str) -> ValidationResult {
+        let json: Option<serde_json::Value> = serde_json::from_str(response.trim()).ok();
+
+        match json {
+            Some(json) => ValidationResult {
+                passed: json["passed"].as_bool().unwrap_or(false),
+                confidence: json["confidence"].as_f64().map(|c| c as f32).unwrap_or(0.0),
+                issues: json["issues"]
+                    .as_array()
+                    .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                    .unwrap_or_default(),
+                suggestions: json["suggestions"]
+                    .as_array()
+                    .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                    .unwrap_or_default(),
+            },
+            None => ValidationResult {
+                passed: false,
+                confidence: 0.0,
+                issues: vec!["Judge model did not return valid JSON".to_string()],
+                suggestions: Vec::new(),
+            },
         }
     }
 
-    fn constraint_violated(&self, output: &str, constraint: &str) -> bool {
-        let constraint_words: Vec<&str> = constraint
-            .split_whitespace()
-            .filter(|w| w.len() > 2)
-            .collect();
+    /// Generate-critique-revise loop: validates `draft` against `constraints`,
+    /// and while it fails with confidence below `min_confidence_threshold`,
+    /// asks `provider` to revise it from the critique's issues/suggestions,
+    /// up to `max_rounds` times. Returns the best draft produced (the last
+    /// one judged, whether or not it ultimately passed) along with its
+    /// `ValidationResult`.
+    pub fn generate_critique_revise(
+        &self,
+        context: &AlgorithmContext,
+        mut draft: String,
+        provider: &dyn CompletionProvider,
+        max_rounds: usize,
+    ) -> (String, ValidationResult) {
+        let mut result = self.validate_output(&draft, &context.constraints, provider);
+
+        for _ in 0..max_rounds {
+            if result.passed || result.confidence >= self.min_confidence_threshold {
+                break;
+            }
 
-        let output_lower = output.to_lowercase();
-        
-        constraint_words.iter().any(|word| output_lower.contains(&word.to_lowercase()))
-    }
+            let revision_prompt = format!(
+                r#"Revise the draft response below so it satisfies all constraints.
+
+Original Requirements:
+{}
+
+Constraints:
+{}
+
+Issues found:
+{}
+
+Suggestions:
+{}
+
+Draft to revise:
+{}
+
+Respond with ONLY the revised draft, no commentary."#,
+                context.user_requirements,
+                context.constraints.join("\n"),
+                result.issues.join("\n"),
+                result.suggestions.join("\n"),
+                draft
+            );
+
+            match provider.complete(&revision_prompt, &CompletionOptions { max_tokens: 1024 }) {
+                Ok(revised) => draft = revised,
+                Err(_) => break,
+            }
 
-    fn constraint_satisfied(&self, output: &str, constraint: &str) -> bool {
-        true
+            result = self.validate_output(&draft, &context.constraints, provider);
+        }
+
+        (draft, result)
     }
 
     pub fn reflect(&self, output: &str, requirements: &str) -> String {
@@ -178,7 +260,7 @@ impl AlgorithmFramework {
         reflection
     }
 
-    pub fn rehearse(&self, context: &AlgorithmContext, settings: &Settings) -> Option<String> {
+    pub fn rehearse(&self, context: &AlgorithmContext, provider: &dyn CompletionProvider) -> Option<String> {
         let prompt = format!(
             r#"You are validating an AI response before it's sent to the user.
 
@@ -199,78 +281,88 @@ Respond with your analysis."#,
             context.plan
         );
 
-        self.call_ai_validation(&prompt, settings)
+        provider.complete(&prompt, &CompletionOptions { max_tokens: 512 }).ok()
     }
+}
 
-    fn call_ai_validation(&self, prompt: &str, settings: &Settings) -> Option<String> {
-        let api_key = if !settings.anthropic_api_key.is_empty() {
-            settings.anthropic_api_key.clone()
-        } else if !settings.openai_api_key.is_empty() {
-            settings.openai_api_key.clone()
-        } else {
-            return None;
-        };
+impl Default for AlgorithmFramework {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        let provider = if !settings.anthropic_api_key.is_empty() {
-            "anthropic"
-        } else {
-            "openai"
-        };
+/// Result of running `AlgorithmFramework::generate_critique_revise` on one
+/// assistant response, sent back to the UI after the fact.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationOutcome {
+    pub original: String,
+    pub revised: String,
+    pub result: ValidationResult,
+}
 
-        let client = reqwest::blocking::Client::new();
-        
-        let body = if provider == "anthropic" {
-            serde_json::json!({
-                "model": "claude-3-haiku-20240307",
-                "max_tokens": 512,
-                "messages": [{
-                    "role": "user",
-                    "content": prompt
-                }]
-            })
-        } else {
-            serde_json::json!({
-                "model": "gpt-4o-mini",
-                "max_tokens": 512,
-                "messages": [{
-                    "role": "user",
-                    "content": prompt
-                }]
-            })
-        };
+/// Runs `AlgorithmFramework::generate_critique_revise` on a `threadpool`
+/// sized to the number of logical cores, so `ai::chat`/`chat_internal` never
+/// blocks the Tokio runtime on it — mirrors `hooks::ExtractionQueue`, down to
+/// the single merge thread that drains results and emits them to the UI.
+pub struct ValidationQueue {
+    pool: ThreadPool,
+    sender: Mutex<Option<Sender<ValidationOutcome>>>,
+}
 
-        let url = if provider == "anthropic" {
-            "https://api.anthropic.com/v1/messages"
-        } else {
-            "https://api.openai.com/v1/chat/completions"
-        };
+impl ValidationQueue {
+    pub fn new() -> Self {
+        Self {
+            pool: ThreadPool::new(num_cpus::get().max(1)),
+            sender: Mutex::new(None),
+        }
+    }
 
-        let request = if provider == "anthropic" {
-            client.post(url)
-                .header("x-api-key", &api_key)
-                .header("anthropic-version", "2023-06-01")
-                .header("content-type", "application/json")
-        } else {
-            client.post(url)
-                .header("authorization", format!("Bearer {}", api_key))
-                .header("content-type", "application/json")
-        };
+    /// Starts the thread that forwards finished validations to the UI as a
+    /// `response-validated` event. Must be called once during app setup
+    /// before `submit` can do anything.
+    pub fn spawn_merge_worker(app_handle: AppHandle) {
+        let (tx, rx) = channel::<ValidationOutcome>();
+        {
+            let state = app_handle.state::<crate::AppState>();
+            if let Ok(mut sender) = state.validation_queue.sender.lock() {
+                *sender = Some(tx);
+            }
+        }
 
-        let response = request.json(&body).send().ok()?;
+        std::thread::spawn(move || {
+            for outcome in rx {
+                let _ = app_handle.emit("response-validated", &outcome);
+            }
+        });
+    }
 
-        let json: serde_json::Value = response.json().ok()?;
-        
-        let content = if provider == "anthropic" {
-            json["content"][0]["text"].as_str()?.to_string()
-        } else {
-            json["choices"][0]["message"]["content"].as_str()?.to_string()
+    /// Enqueues `draft` for background generate-critique-revise against
+    /// `context.constraints`. A no-op if there's no merge worker running yet.
+    pub fn submit(
+        &self,
+        context: AlgorithmContext,
+        draft: String,
+        framework: Arc<AlgorithmFramework>,
+        provider: Box<dyn CompletionProvider>,
+    ) {
+        let sender = match self.sender.lock().ok().and_then(|s| s.clone()) {
+            Some(sender) => sender,
+            None => return,
         };
 
-        Some(content)
+        self.pool.execute(move || {
+            let (revised, result) =
+                framework.generate_critique_revise(&context, draft.clone(), provider.as_ref(), 2);
+            let _ = sender.send(ValidationOutcome {
+                original: draft,
+                revised,
+                result,
+            });
+        });
     }
 }
 
-impl Default for AlgorithmFramework {
+impl Default for ValidationQueue {
     fn default() -> Self {
         Self::new()
     }
@@ -0,0 +1,199 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// dirstate-v2-style docket: one small metadata file recording where each
+/// source file's already-parsed record lives in the companion packed data
+/// file, so a reload only has to `stat()` disk files instead of re-reading
+/// and re-parsing all of them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Docket {
+    generation: u64,
+    entries: Vec<DocketEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DocketEntry {
+    relative_path: String,
+    mtime: i64,
+    size: u64,
+    offset: u64,
+    len: u64,
+}
+
+fn get_cache_dir() -> PathBuf {
+    crate::memory::get_base_dir().join("cache")
+}
+
+fn file_mtime(metadata: &fs::Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Returns the process-wide lock for cache `name`, so two `#[tauri::command]`s
+/// hitting the same `DiskCache` concurrently (e.g. a search racing a
+/// save-triggered reload) serialize their read-then-maybe-rewrite sequence
+/// instead of interleaving two `rewrite()` calls.
+fn cache_lock(name: &str) -> Arc<Mutex<()>> {
+    static LOCKS: OnceLock<Mutex<HashMap<String, Arc<Mutex<()>>>>> = OnceLock::new();
+    let locks = LOCKS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut locks = locks.lock().unwrap_or_else(|e| e.into_inner());
+    locks.entry(name.to_string()).or_insert_with(|| Arc::new(Mutex::new(()))).clone()
+}
+
+/// Writes `bytes` to `path` atomically: writes to a sibling temp file first,
+/// then renames it into place, so a reader never observes a partially
+/// written file even if two writers race.
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("cache");
+    let tmp_path = path.with_file_name(format!("{file_name}.tmp-{}", std::process::id()));
+    fs::write(&tmp_path, bytes)?;
+    fs::rename(&tmp_path, path)
+}
+
+/// A docket + packed-data disk cache for parsed records of a given kind
+/// (`name` picks the `{name}.docket`/`{name}.data` filenames, e.g.
+/// `"memories"` or `"work_items"`). Shared by `memory::load_memories_from_disk_internal`
+/// and `memory::get_work_items` so both get incremental reloads without
+/// duplicating the docket bookkeeping.
+pub struct DiskCache {
+    name: String,
+    docket_path: PathBuf,
+    data_path: PathBuf,
+}
+
+impl DiskCache {
+    pub fn new(name: &str) -> Self {
+        let dir = get_cache_dir();
+        Self {
+            name: name.to_string(),
+            docket_path: dir.join(format!("{name}.docket")),
+            data_path: dir.join(format!("{name}.data")),
+        }
+    }
+
+    fn read_docket(&self) -> Docket {
+        fs::read_to_string(&self.docket_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Returns one `T` per path in `files`, in the same order, reusing the
+    /// packed record for any file whose mtime and size still match the
+    /// docket and falling back to `parse` for new, changed, or missing-from-cache
+    /// files. Rewrites the docket and packed data file only when at least one
+    /// file needed reparsing or a previously cached file disappeared.
+    pub fn load<T, F>(&self, base_dir: &Path, files: &[PathBuf], parse: F) -> Vec<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Fn(&Path) -> Option<T>,
+    {
+        let lock = cache_lock(&self.name);
+        let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let docket = self.read_docket();
+        let by_path: HashMap<&str, &DocketEntry> =
+            docket.entries.iter().map(|e| (e.relative_path.as_str(), e)).collect();
+        let packed = fs::read(&self.data_path).unwrap_or_default();
+
+        let mut items = Vec::with_capacity(files.len());
+        // (relative_path, mtime, size) for every item actually kept, in the
+        // same order as `items`, so a rewrite can rebuild the docket without
+        // re-`stat()`ing anything.
+        let mut stats = Vec::with_capacity(files.len());
+        let mut reparsed = false;
+
+        for path in files {
+            let relative_path = path
+                .strip_prefix(base_dir)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .into_owned();
+
+            let metadata = match fs::metadata(path) {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            let mtime = file_mtime(&metadata);
+            let size = metadata.len();
+
+            let cached = by_path.get(relative_path.as_str()).and_then(|entry| {
+                if entry.mtime != mtime || entry.size != size {
+                    return None;
+                }
+                let start = entry.offset as usize;
+                let end = start + entry.len as usize;
+                packed.get(start..end).and_then(|bytes| serde_json::from_slice::<T>(bytes).ok())
+            });
+
+            let item = match cached {
+                Some(item) => item,
+                None => {
+                    reparsed = true;
+                    match parse(path) {
+                        Some(item) => item,
+                        None => continue,
+                    }
+                }
+            };
+
+            items.push(item);
+            stats.push((relative_path, mtime, size));
+        }
+
+        let shrank = docket.entries.len() != stats.len();
+        if reparsed || shrank {
+            self.rewrite(docket.generation, &stats, &items);
+        }
+
+        items
+    }
+
+    /// Packs `items` as JSON records and writes a fresh docket pointing at
+    /// their offsets, bumping `generation` so callers can tell a rewrite
+    /// happened. Both files are written to a sibling temp file and renamed
+    /// into place, so a concurrent reader never sees a truncated data file
+    /// or a docket pointing into one. Callers must hold `cache_lock(&self.name)`.
+    fn rewrite<T: Serialize>(&self, generation: u64, stats: &[(String, i64, u64)], items: &[T]) {
+        if let Some(parent) = self.data_path.parent() {
+            if fs::create_dir_all(parent).is_err() {
+                return;
+            }
+        }
+
+        let mut packed = Vec::new();
+        let mut entries = Vec::with_capacity(items.len());
+
+        for ((relative_path, mtime, size), item) in stats.iter().zip(items) {
+            let Ok(bytes) = serde_json::to_vec(item) else { continue };
+            let offset = packed.len() as u64;
+            let len = bytes.len() as u64;
+            packed.extend_from_slice(&bytes);
+
+            entries.push(DocketEntry {
+                relative_path: relative_path.clone(),
+                mtime: *mtime,
+                size: *size,
+                offset,
+                len,
+            });
+        }
+
+        let docket = Docket { generation: generation + 1, entries };
+        if write_atomic(&self.data_path, &packed).is_err() {
+            return;
+        }
+        if let Ok(json) = serde_json::to_string(&docket) {
+            let _ = write_atomic(&self.docket_path, json.as_bytes());
+        }
+    }
+}
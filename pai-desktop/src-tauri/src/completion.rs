@@ -0,0 +1,202 @@
+use crate::Settings;
+
+/// Parameters for a single blocking completion request, independent of
+/// which provider ultimately serves it.
+#[derive(Debug, Clone)]
+pub struct CompletionOptions {
+    pub max_tokens: u32,
+}
+
+impl Default for CompletionOptions {
+    fn default() -> Self {
+        Self { max_tokens: 1024 }
+    }
+}
+
+/// A provider capable of turning a single prompt into a single completion,
+/// used by the algorithm and hook subsystems for small, synchronous "ask a
+/// cheap model" calls (validation critiques, memory extraction). This is
+/// deliberately simpler than `ai::LlmClient`, which remains the richer
+/// async/streaming/tool-calling path used by `ai::chat`.
+///
+/// Object-safe and `box_clone`-able so `Box<dyn CompletionProvider>` can
+/// implement `Clone` and be stored in `AppState`.
+pub trait CompletionProvider: Send + Sync {
+    /// The small/cheap model this provider defaults to for background calls.
+    fn fast_model(&self) -> &str;
+
+    fn complete(&self, prompt: &str, opts: &CompletionOptions) -> Result<String, String>;
+
+    fn box_clone(&self) -> Box<dyn CompletionProvider>;
+}
+
+impl Clone for Box<dyn CompletionProvider> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+#[derive(Clone)]
+pub struct AnthropicProvider {
+    pub api_key: String,
+}
+
+impl CompletionProvider for AnthropicProvider {
+    fn fast_model(&self) -> &str {
+        "claude-3-haiku-20240307"
+    }
+
+    fn complete(&self, prompt: &str, opts: &CompletionOptions) -> Result<String, String> {
+        let client = reqwest::blocking::Client::new();
+        let body = serde_json::json!({
+            "model": self.fast_model(),
+            "max_tokens": opts.max_tokens,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+
+        let response = client
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&body)
+            .send()
+            .map_err(|e| e.to_string())?;
+
+        let json: serde_json::Value = response.json().map_err(|e| e.to_string())?;
+        json["content"][0]["text"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Anthropic response missing content".to_string())
+    }
+
+    fn box_clone(&self) -> Box<dyn CompletionProvider> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct OpenAiProvider {
+    pub api_key: String,
+}
+
+impl CompletionProvider for OpenAiProvider {
+    fn fast_model(&self) -> &str {
+        "gpt-4o-mini"
+    }
+
+    fn complete(&self, prompt: &str, opts: &CompletionOptions) -> Result<String, String> {
+        let client = reqwest::blocking::Client::new();
+        let body = serde_json::json!({
+            "model": self.fast_model(),
+            "max_tokens": opts.max_tokens,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+
+        let response = client
+            .post("https://api.openai.com/v1/chat/completions")
+            .header("authorization", format!("Bearer {}", self.api_key))
+            .json(&body)
+            .send()
+            .map_err(|e| e.to_string())?;
+
+        let json: serde_json::Value = response.json().map_err(|e| e.to_string())?;
+        json["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "OpenAI response missing content".to_string())
+    }
+
+    fn box_clone(&self) -> Box<dyn CompletionProvider> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct GoogleProvider {
+    pub api_key: String,
+}
+
+impl CompletionProvider for GoogleProvider {
+    fn fast_model(&self) -> &str {
+        "gemini-1.5-flash"
+    }
+
+    fn complete(&self, prompt: &str, opts: &CompletionOptions) -> Result<String, String> {
+        let client = reqwest::blocking::Client::new();
+        let url = format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
+            self.fast_model(),
+            self.api_key
+        );
+        let body = serde_json::json!({
+            "contents": [{ "parts": [{ "text": prompt }] }],
+            "generationConfig": { "maxOutputTokens": opts.max_tokens }
+        });
+
+        let response = client.post(&url).json(&body).send().map_err(|e| e.to_string())?;
+        let json: serde_json::Value = response.json().map_err(|e| e.to_string())?;
+        json["candidates"][0]["content"]["parts"][0]["text"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Google response missing content".to_string())
+    }
+
+    fn box_clone(&self) -> Box<dyn CompletionProvider> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct XaiProvider {
+    pub api_key: String,
+}
+
+impl CompletionProvider for XaiProvider {
+    fn fast_model(&self) -> &str {
+        "grok-beta"
+    }
+
+    fn complete(&self, prompt: &str, opts: &CompletionOptions) -> Result<String, String> {
+        let client = reqwest::blocking::Client::new();
+        let body = serde_json::json!({
+            "model": self.fast_model(),
+            "max_tokens": opts.max_tokens,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+
+        let response = client
+            .post("https://api.x.ai/v1/chat/completions")
+            .header("authorization", format!("Bearer {}", self.api_key))
+            .json(&body)
+            .send()
+            .map_err(|e| e.to_string())?;
+
+        let json: serde_json::Value = response.json().map_err(|e| e.to_string())?;
+        json["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "xAI response missing content".to_string())
+    }
+
+    fn box_clone(&self) -> Box<dyn CompletionProvider> {
+        Box::new(self.clone())
+    }
+}
+
+/// Picks a provider from whichever API key is configured first, in the order
+/// Anthropic, OpenAI, Google, xAI. `settings.default_provider` is not consulted.
+pub fn build_completion_provider(settings: &Settings) -> Option<Box<dyn CompletionProvider>> {
+    if !settings.anthropic_api_key.is_empty() {
+        return Some(Box::new(AnthropicProvider { api_key: settings.anthropic_api_key.clone() }));
+    }
+    if !settings.openai_api_key.is_empty() {
+        return Some(Box::new(OpenAiProvider { api_key: settings.openai_api_key.clone() }));
+    }
+    if !settings.google_api_key.is_empty() {
+        return Some(Box::new(GoogleProvider { api_key: settings.google_api_key.clone() }));
+    }
+    if !settings.xai_api_key.is_empty() {
+        return Some(Box::new(XaiProvider { api_key: settings.xai_api_key.clone() }));
+    }
+    None
+}
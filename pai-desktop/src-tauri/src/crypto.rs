@@ -0,0 +1,123 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, OsRng};
+use chacha20poly1305::{AeadCore, KeyInit, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+pub const KEY_LEN: usize = 32;
+pub const SALT_LEN: usize = 16;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyFile {
+    salt: String,
+    verifier: String,
+}
+
+pub fn get_keyfile_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("PAI")
+        .join("keyfile")
+}
+
+/// Derives a symmetric key from a passphrase via Argon2id using the given salt.
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], String> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+/// Derives a key for a fresh passphrase, writing a new salt + verification
+/// tag to the keyfile so future unlocks can validate the passphrase first.
+pub fn initialize_keyfile(passphrase: &str) -> Result<[u8; KEY_LEN], String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let key = derive_key(passphrase, &salt)?;
+    let verifier = derive_key(passphrase, &key)?;
+
+    let keyfile = KeyFile {
+        salt: base64_encode(&salt),
+        verifier: base64_encode(&verifier),
+    };
+
+    let path = get_keyfile_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&keyfile).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| e.to_string())?;
+
+    Ok(key)
+}
+
+/// Validates a passphrase against the stored keyfile and returns the derived key.
+pub fn unlock_with_passphrase(passphrase: &str) -> Result<[u8; KEY_LEN], String> {
+    let path = get_keyfile_path();
+    if !path.exists() {
+        return initialize_keyfile(passphrase);
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let keyfile: KeyFile = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+    let salt = base64_decode(&keyfile.salt)?;
+    let key = derive_key(passphrase, &salt)?;
+
+    let expected_verifier = base64_decode(&keyfile.verifier)?;
+    let verifier = derive_key(passphrase, &key)?;
+
+    if verifier.as_slice() != expected_verifier.as_slice() {
+        return Err("Incorrect passphrase".to_string());
+    }
+
+    Ok(key)
+}
+
+pub struct EncryptedBlob {
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+pub fn encrypt(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Result<EncryptedBlob, String> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|e| e.to_string())?;
+
+    Ok(EncryptedBlob {
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })
+}
+
+/// Decrypts a nonce‖ciphertext pair. Returns `Err` on authentication failure
+/// so callers can skip tampered or corrupted entries instead of crashing.
+pub fn decrypt(key: &[u8; KEY_LEN], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+    // `XNonce::from_slice` panics on a wrong-length slice.
+    if nonce.len() != 24 {
+        return Err("Failed to authenticate message".to_string());
+    }
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XNonce::from_slice(nonce);
+    cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|_| "Failed to authenticate message".to_string())
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+fn base64_decode(s: &str) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD
+        .decode(s)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,61 @@
+use reqwest::Client;
+
+/// Embeds `text` via OpenAI's embeddings endpoint and L2-normalizes the
+/// result so that later similarity checks reduce to a plain dot product.
+pub async fn embed_openai(api_key: &str, text: &str) -> Result<Vec<f32>, String> {
+    embed("https://api.openai.com/v1/embeddings", api_key, "text-embedding-3-small", text).await
+}
+
+/// Embeds `text` against any OpenAI-compatible `/v1/embeddings` endpoint
+/// (OpenAI, a local Ollama/LM Studio/llama.cpp server, ...), used by
+/// `memory::search_memories_semantic` with `Settings::embedding_api_url`.
+/// `api_key` may be empty for endpoints that don't require one.
+pub async fn embed(api_url: &str, api_key: &str, model: &str, text: &str) -> Result<Vec<f32>, String> {
+    let client = Client::new();
+    let mut request = client.post(api_url).json(&serde_json::json!({
+        "model": model,
+        "input": text,
+    }));
+    if !api_key.is_empty() {
+        request = request.header("Authorization", format!("Bearer {}", api_key));
+    }
+
+    let response = request.send().await.map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Embeddings API error ({}): {}", status, text));
+    }
+
+    let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    let values = json
+        .get("data")
+        .and_then(|d| d.get(0))
+        .and_then(|d| d.get("embedding"))
+        .and_then(|e| e.as_array())
+        .ok_or("Malformed embeddings response")?;
+
+    let mut vector: Vec<f32> = values
+        .iter()
+        .filter_map(|v| v.as_f64())
+        .map(|v| v as f32)
+        .collect();
+
+    normalize(&mut vector);
+    Ok(vector)
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for v in vector.iter_mut() {
+            *v /= norm;
+        }
+    }
+}
+
+/// Dot product of two pre-normalized vectors, i.e. their cosine similarity.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
@@ -1,4 +1,12 @@
-use crate::{ChatMessage, MemoryItem, Settings};
+use crate::completion::{CompletionOptions, CompletionProvider};
+use crate::{AppState, ChatMessage, MemoryItem};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use threadpool::ThreadPool;
 
 pub struct HookSystem {
     message_count_threshold: usize,
@@ -41,33 +49,49 @@ impl HookSystem {
         }
     }
 
-    pub fn check_and_extract_memory(&self, messages: &[ChatMessage], settings: &Settings) -> Option<MemoryItem> {
+    /// Scans recent messages for extraction triggers and returns the
+    /// candidate windows that should be handed to an `ExtractionQueue`.
+    /// This is pure and synchronous — it never calls the LLM itself, so it's
+    /// safe to run inline on `ai::chat`'s thread.
+    pub fn candidate_windows(&self, messages: &[ChatMessage]) -> Vec<(String, ExtractionKind)> {
+        let mut windows = Vec::new();
         let recent_messages: Vec<_> = messages.iter().rev().take(10).collect();
-        
+
         for msg in &recent_messages {
             let content_lower = msg.content.to_lowercase();
-            
-            for keyword in &self.keywords {
-                if content_lower.contains(&keyword.to_lowercase()) {
-                    if let Some(memory) = self.analyze_and_extract(&msg.content, settings) {
-                        return Some(memory);
-                    }
-                }
+            if self.keywords.iter().any(|k| content_lower.contains(&k.to_lowercase())) {
+                windows.push((msg.content.clone(), ExtractionKind::Keyword));
             }
         }
 
-        if messages.len() >= self.message_count_threshold {
+        if self.should_auto_extract(messages.len()) {
             if let Some(last_msg) = recent_messages.first() {
                 if last_msg.role == "user" && last_msg.content.len() > 50 {
-                    return self.analyze_contextual_memory(messages, settings);
+                    let conversation: String = recent_messages
+                        .iter()
+                        .take(5)
+                        .map(|m| format!("{}: {}", m.role, m.content))
+                        .collect::<Vec<_>>()
+                        .join("\n\n");
+                    windows.push((conversation, ExtractionKind::Contextual));
                 }
             }
         }
 
-        None
+        windows
+    }
+
+    /// Runs the LLM extraction for one candidate window. Blocking — callers
+    /// that care about latency should run this on an `ExtractionQueue`
+    /// worker rather than inline.
+    pub fn analyze_window(&self, window: &str, kind: ExtractionKind, provider: &dyn CompletionProvider) -> Option<MemoryItem> {
+        match kind {
+            ExtractionKind::Keyword => self.analyze_and_extract(window, provider),
+            ExtractionKind::Contextual => self.analyze_contextual_window(window, provider),
+        }
     }
 
-    fn analyze_and_extract(&self, content: &str, settings: &Settings) -> Option<MemoryItem> {
+    fn analyze_and_extract(&self, content: &str, provider: &dyn CompletionProvider) -> Option<MemoryItem> {
         let prompt = format!(
             r#"Analyze the following text and extract important information as a memory item.
 Return a JSON object with these fields:
@@ -83,20 +107,12 @@ Respond with ONLY valid JSON, no other text."#,
             content
         );
 
-        let response = self.call_ai_for_extraction(&prompt, settings)?;
+        let response = provider.complete(&prompt, &CompletionOptions { max_tokens: 1024 }).ok()?;
 
         self.parse_memory_response(&response)
     }
 
-    fn analyze_contextual_memory(&self, messages: &[ChatMessage], settings: &Settings) -> Option<MemoryItem> {
-        let conversation: String = messages
-            .iter()
-            .rev()
-            .take(5)
-            .map(|m| format!("{}: {}", m.role, m.content))
-            .collect::<Vec<_>>()
-            .join("\n\n");
-
+    fn analyze_contextual_window(&self, conversation: &str, provider: &dyn CompletionProvider) -> Option<MemoryItem> {
         let prompt = format!(
             r#"Analyze the following conversation and extract any important information that should be remembered.
 Look for:
@@ -120,10 +136,10 @@ If nothing important found, respond with: {{"title": "", "content": "", "memory_
             conversation
         );
 
-        let response = self.call_ai_for_extraction(&prompt, settings)?;
+        let response = provider.complete(&prompt, &CompletionOptions { max_tokens: 1024 }).ok()?;
 
         let memory = self.parse_memory_response(&response)?;
-        
+
         if memory.title.is_empty() {
             return None;
         }
@@ -131,76 +147,6 @@ If nothing important found, respond with: {{"title": "", "content": "", "memory_
         Some(memory)
     }
 
-    fn call_ai_for_extraction(&self, prompt: &str, settings: &Settings) -> Option<String> {
-        let api_key = if !settings.anthropic_api_key.is_empty() {
-            settings.anthropic_api_key.clone()
-        } else if !settings.openai_api_key.is_empty() {
-            settings.openai_api_key.clone()
-        } else {
-            return None;
-        };
-
-        let provider = if !settings.anthropic_api_key.is_empty() {
-            "anthropic"
-        } else {
-            "openai"
-        };
-
-        let client = reqwest::blocking::Client::new();
-        
-        let body = if provider == "anthropic" {
-            serde_json::json!({
-                "model": "claude-3-haiku-20240307",
-                "max_tokens": 1024,
-                "messages": [{
-                    "role": "user",
-                    "content": prompt
-                }]
-            })
-        } else {
-            serde_json::json!({
-                "model": "gpt-4o-mini",
-                "max_tokens": 1024,
-                "messages": [{
-                    "role": "user",
-                    "content": prompt
-                }]
-            })
-        };
-
-        let url = if provider == "anthropic" {
-            "https://api.anthropic.com/v1/messages"
-        } else {
-            "https://api.openai.com/v1/chat/completions"
-        };
-
-        let request = if provider == "anthropic" {
-            client.post(url)
-                .header("x-api-key", &api_key)
-                .header("anthropic-version", "2023-06-01")
-                .header("content-type", "application/json")
-        } else {
-            client.post(url)
-                .header("authorization", format!("Bearer {}", api_key))
-                .header("content-type", "application/json")
-        };
-
-        let response = request
-            .json(&body)
-            .send()
-            .ok()?;
-
-        let json: serde_json::Value = response.json().ok()?;
-        
-        let content = if provider == "anthropic" {
-            json["content"][0]["text"].as_str()?.to_string()
-        } else {
-            json["choices"][0]["message"]["content"].as_str()?.to_string()
-        };
-
-        Some(content)
-    }
-
     fn parse_memory_response(&self, response: &str) -> Option<MemoryItem> {
         let json_str = response.trim();
         let json: serde_json::Value = serde_json::from_str(json_str).ok()?;
@@ -208,7 +154,7 @@ If nothing important found, respond with: {{"title": "", "content": "", "memory_
         let title = json["title"].as_str()?.to_string();
         let content = json["content"].as_str()?.to_string();
         let memory_type = json["memory_type"].as_str()?.to_string();
-        
+
         let tags: Vec<String> = json["tags"]
             .as_array()?
             .iter()
@@ -224,6 +170,7 @@ If nothing important found, respond with: {{"title": "", "content": "", "memory_
             tags,
             entities: Vec::new(),
             confidence: 0.8,
+            embedding: None,
         })
     }
 
@@ -237,3 +184,114 @@ impl Default for HookSystem {
         Self::new()
     }
 }
+
+/// Which prompt `ExtractionQueue` should run for a candidate window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtractionKind {
+    Keyword,
+    Contextual,
+}
+
+/// Runs `HookSystem`'s LLM-based memory extraction on a `threadpool` sized
+/// to the number of logical cores, so `ai::chat` never blocks on it.
+/// Extracted memories are sent back over a channel to a single merge thread
+/// (started by `spawn_merge_worker`) that deduplicates against
+/// `AppState.memories` before saving. Submitting the same text window twice
+/// while the first job is still running is a no-op — in-flight jobs are
+/// tracked by a hash of the window text.
+pub struct ExtractionQueue {
+    pool: ThreadPool,
+    in_flight: Arc<Mutex<HashSet<u64>>>,
+    sender: Mutex<Option<Sender<MemoryItem>>>,
+}
+
+impl ExtractionQueue {
+    pub fn new() -> Self {
+        Self {
+            pool: ThreadPool::new(num_cpus::get().max(1)),
+            in_flight: Arc::new(Mutex::new(HashSet::new())),
+            sender: Mutex::new(None),
+        }
+    }
+
+    /// Starts the thread that drains extracted memories into
+    /// `AppState.memories`, mirroring `messages::spawn_message_watcher`.
+    /// Must be called once during app setup before `submit` can do anything.
+    pub fn spawn_merge_worker(app_handle: AppHandle) {
+        let (tx, rx) = channel::<MemoryItem>();
+        {
+            let state = app_handle.state::<AppState>();
+            if let Ok(mut sender) = state.extraction_queue.sender.lock() {
+                *sender = Some(tx);
+            }
+        }
+
+        std::thread::spawn(move || {
+            for memory in rx {
+                let state = app_handle.state::<AppState>();
+                let existing = match state.memories.lock() {
+                    Ok(guard) => guard.clone(),
+                    Err(_) => continue,
+                };
+                if crate::memory::is_near_duplicate(&memory, &existing) {
+                    continue;
+                }
+                if let Err(e) = crate::memory::save_memory_internal(&memory) {
+                    log::warn!("Failed to persist extracted memory {}: {}", memory.id, e);
+                    continue;
+                }
+                if let Ok(mut memories) = state.memories.lock() {
+                    memories.push(memory);
+                }
+                let _ = app_handle.emit("memories-updated", ());
+            }
+        });
+    }
+
+    /// Enqueues `window` for background extraction unless an identical
+    /// window is already being processed.
+    pub fn submit(&self, window: String, kind: ExtractionKind, hooks: Arc<HookSystem>, provider: Box<dyn CompletionProvider>) {
+        let key = hash_window(&window);
+        {
+            let mut in_flight = match self.in_flight.lock() {
+                Ok(guard) => guard,
+                Err(_) => return,
+            };
+            if !in_flight.insert(key) {
+                return;
+            }
+        }
+
+        let sender = match self.sender.lock().ok().and_then(|s| s.clone()) {
+            Some(sender) => sender,
+            None => {
+                if let Ok(mut in_flight) = self.in_flight.lock() {
+                    in_flight.remove(&key);
+                }
+                return;
+            }
+        };
+
+        let in_flight = self.in_flight.clone();
+        self.pool.execute(move || {
+            if let Some(memory) = hooks.analyze_window(&window, kind, provider.as_ref()) {
+                let _ = sender.send(memory);
+            }
+            if let Ok(mut in_flight) = in_flight.lock() {
+                in_flight.remove(&key);
+            }
+        });
+    }
+}
+
+impl Default for ExtractionQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn hash_window(window: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    window.hash(&mut hasher);
+    hasher.finish()
+}
@@ -3,18 +3,44 @@ use std::sync::Mutex;
 
 pub mod algorithm;
 pub mod ai;
+pub mod cache;
+pub mod completion;
+pub mod crypto;
+pub mod embeddings;
 pub mod hooks;
 pub mod memory;
 pub mod messages;
+pub mod permissions;
+pub mod roles;
+pub mod search;
 pub mod session;
 pub mod settings;
 pub mod skills;
+pub mod tokenizer;
 
 #[derive(Default)]
 pub struct AppState {
     pub settings: Mutex<Settings>,
     pub memories: Mutex<Vec<MemoryItem>>,
     pub messages: Mutex<Vec<ChatMessage>>,
+    /// Symmetric key derived from the user's passphrase, held only while unlocked.
+    pub encryption_key: Mutex<Option<[u8; crypto::KEY_LEN]>>,
+    /// Running per-model/per-session token and cost totals.
+    pub usage: Mutex<ai::UsageStats>,
+    /// Shared `CompletionProvider` used by the algorithm/hook subsystems,
+    /// rebuilt whenever settings change so a new API key takes effect
+    /// without restarting the app.
+    pub completion_provider: Mutex<Option<Box<dyn completion::CompletionProvider>>>,
+    /// Background worker pool that runs hook-driven memory extraction off
+    /// the chat thread; see `hooks::ExtractionQueue`.
+    pub extraction_queue: hooks::ExtractionQueue,
+    /// Background worker pool that runs the generate-critique-revise loop
+    /// off the chat thread; see `algorithm::ValidationQueue`.
+    pub validation_queue: algorithm::ValidationQueue,
+    /// The in-memory-only session created by `session::create_temp_session`,
+    /// alongside its transcript. Never touches `session::get_sessions_dir()`
+    /// unless flushed via `session::promote_temp_session`.
+    pub temp_session: Mutex<Option<(session::Session, Vec<session::Message>)>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,10 +48,32 @@ pub struct ChatMessage {
     pub role: String,
     pub content: String,
     pub timestamp: i64,
+    #[serde(default)]
+    pub conversation_id: Option<String>,
+    /// Token usage for this call; only set on assistant messages.
+    #[serde(default)]
+    pub usage: Option<ai::TokenUsage>,
+    /// Token count of `content` under the model's tokenizer, so the UI can
+    /// show how much of the context window a message occupies.
+    #[serde(default)]
+    pub token_count: Option<usize>,
+}
+
+/// Current `Settings` on-disk schema version. Bump this and append a new
+/// `vN_to_vN+1` migration in `settings::MIGRATIONS` whenever a field is
+/// added, renamed, or removed in a way that breaks a straight deserialize.
+pub const CURRENT_SETTINGS_VERSION: u32 = 2;
+
+fn default_settings_version() -> u32 {
+    CURRENT_SETTINGS_VERSION
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
+    /// Schema version, migrated forward by `settings::load_settings_from_disk`.
+    /// Missing on files predating this field, which `serde(default)` reads as `0`.
+    #[serde(default)]
+    pub version: u32,
     pub anthropic_api_key: String,
     pub openai_api_key: String,
     pub google_api_key: String,
@@ -33,12 +81,36 @@ pub struct Settings {
     pub perplexity_api_key: String,
     pub elevenlabs_api_key: String,
     pub default_model: String,
+    /// Key into the built-in provider registry ("anthropic", "openai",
+    /// "google", "xai", "perplexity") or the `name` of a `custom_providers`
+    /// entry. Replaces guessing the provider from the model-name prefix.
+    #[serde(default = "default_provider")]
+    pub default_provider: String,
+    #[serde(default)]
+    pub custom_providers: Vec<CustomProviderConfig>,
     pub voice_enabled: bool,
+    /// Endpoint used by `memory::search_memories_semantic`/`reindex_embeddings`
+    /// to embed memory text, OpenAI-compatible request/response shape.
+    /// Defaults to a local Ollama instance so semantic search works with no
+    /// API key configured.
+    #[serde(default = "default_embedding_api_url")]
+    pub embedding_api_url: String,
+    #[serde(default)]
+    pub embedding_api_key: String,
+}
+
+fn default_provider() -> String {
+    "anthropic".to_string()
+}
+
+fn default_embedding_api_url() -> String {
+    "http://localhost:11434/v1/embeddings".to_string()
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            version: default_settings_version(),
             anthropic_api_key: String::new(),
             openai_api_key: String::new(),
             google_api_key: String::new(),
@@ -46,11 +118,24 @@ impl Default for Settings {
             perplexity_api_key: String::new(),
             elevenlabs_api_key: String::new(),
             default_model: "claude-sonnet-4-20250514".to_string(),
+            default_provider: default_provider(),
+            custom_providers: Vec::new(),
             voice_enabled: false,
+            embedding_api_url: default_embedding_api_url(),
+            embedding_api_key: String::new(),
         }
     }
 }
 
+/// An arbitrary OpenAI-compatible endpoint (Ollama, LM Studio, OpenRouter,
+/// a self-hosted gateway, ...) registered by the user instead of baked in.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CustomProviderConfig {
+    pub name: String,
+    pub base_url: String,
+    pub api_key: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryItem {
     pub id: String,
@@ -61,6 +146,11 @@ pub struct MemoryItem {
     pub tags: Vec<String>,
     pub entities: Vec<String>,
     pub confidence: f32,
+    /// L2-normalized embedding of `title` + `content`, computed at save time
+    /// when `Settings::openai_api_key` is configured. `None` falls back to
+    /// keyword matching in `ai::build_history`.
+    #[serde(default)]
+    pub embedding: Option<Vec<f32>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,15 +186,81 @@ pub struct Skill {
     pub name: String,
     pub description: String,
     pub category: String,
+    /// Keywords that auto-activate the skill without an explicit invocation.
+    #[serde(default)]
+    pub triggers: Vec<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Other skill ids this skill expects to already be available.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default = "default_skill_enabled")]
+    pub enabled: bool,
+}
+
+fn default_skill_enabled() -> bool {
+    true
+}
+
+/// A reusable system-prompt template that seeds a session, analogous to
+/// aichat's `Role` — distinct from `Skill`, which describes a capability
+/// rather than a prompt to bind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Role {
+    pub id: String,
+    pub name: String,
+    pub prompt: String,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+}
+
+/// One immutable entry in a message's append-only edit history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageRevision {
+    pub message_id: i64,
+    pub revision: i64,
+    pub role: String,
+    pub content: String,
+    pub content_hash: String,
+    pub parent_revision: Option<i64>,
+    pub created_at: i64,
+}
+
+/// A problem found in a memory or work-item file by `memory::diagnose_storage`,
+/// reporting why `parse_markdown_memory`/`parse_work_meta` return a silent
+/// `None` for the same file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageIssue {
+    pub path: String,
+    /// One of: `missing_required_field`, `unparseable_numeric_field`,
+    /// `missing_frontmatter_delimiter`, `duplicate_id`, `unknown_memory_type`.
+    pub category: String,
+    pub message: String,
 }
 
-pub use ai::chat;
+pub use ai::{chat, chat_stream, get_usage_stats};
 pub use settings::{get_settings, save_settings};
-pub use skills::{get_skills, save_skill, get_skill_content, delete_skill};
-pub use session::{get_current_session, create_new_session, list_sessions, switch_session, delete_session, rename_session};
+pub use skills::{get_skills, save_skill, get_skill_content, delete_skill, validate_skill};
+pub use roles::{get_roles, save_role, get_role_content, delete_role, set_session_role};
+pub use permissions::{
+    Capability, capability_new, permission_add, permission_rm, permission_ls,
+    set_session_capability, check_skill_allowed,
+};
+pub use session::{
+    get_current_session, create_new_session, list_sessions, switch_session, delete_session, rename_session,
+    append_message, get_session_messages, clear_session_messages,
+    export_session, import_session,
+    create_temp_session, promote_temp_session,
+};
 pub use memory::{
     get_memories, save_memory, delete_memory, load_memories_from_disk,
-    search_memories, save_relationship_note, get_relationship_notes,
+    search_memories,
+    search_memories_semantic, reindex_embeddings, diagnose_storage,
+    save_relationship_note, get_relationship_notes,
     save_work_item, get_work_items, complete_work_item,
     save_prd, get_prds,
 };
+pub use messages::{
+    get_messages, add_message, clear_messages, unlock, lock, export_messages, import_messages,
+    search_messages, edit_message, get_message_history, revert_message,
+};
@@ -2,12 +2,20 @@
 
 mod algorithm;
 mod ai;
+mod cache;
+mod completion;
+mod crypto;
+mod embeddings;
 mod hooks;
 mod memory;
 mod messages;
+mod permissions;
+mod roles;
+mod search;
 mod settings;
 mod session;
 mod skills;
+mod tokenizer;
 
 use std::sync::Mutex;
 use serde::{Deserialize, Serialize};
@@ -18,6 +26,24 @@ pub struct AppState {
     pub settings: Mutex<Settings>,
     pub memories: Mutex<Vec<MemoryItem>>,
     pub messages: Mutex<Vec<ChatMessage>>,
+    /// Symmetric key derived from the user's passphrase, held only while unlocked.
+    pub encryption_key: Mutex<Option<[u8; crypto::KEY_LEN]>>,
+    /// Running per-model/per-session token and cost totals.
+    pub usage: Mutex<ai::UsageStats>,
+    /// Shared `CompletionProvider` used by the algorithm/hook subsystems,
+    /// rebuilt whenever settings change so a new API key takes effect
+    /// without restarting the app.
+    pub completion_provider: Mutex<Option<Box<dyn completion::CompletionProvider>>>,
+    /// Background worker pool that runs hook-driven memory extraction off
+    /// the chat thread; see `hooks::ExtractionQueue`.
+    pub extraction_queue: hooks::ExtractionQueue,
+    /// Background worker pool that runs the generate-critique-revise loop
+    /// off the chat thread; see `algorithm::ValidationQueue`.
+    pub validation_queue: algorithm::ValidationQueue,
+    /// The in-memory-only session created by `session::create_temp_session`,
+    /// alongside its transcript. Never touches `session::get_sessions_dir()`
+    /// unless flushed via `session::promote_temp_session`.
+    pub temp_session: Mutex<Option<(session::Session, Vec<session::Message>)>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,10 +51,32 @@ pub struct ChatMessage {
     pub role: String,
     pub content: String,
     pub timestamp: i64,
+    #[serde(default)]
+    pub conversation_id: Option<String>,
+    /// Token usage for this call; only set on assistant messages.
+    #[serde(default)]
+    pub usage: Option<ai::TokenUsage>,
+    /// Token count of `content` under the model's tokenizer, so the UI can
+    /// show how much of the context window a message occupies.
+    #[serde(default)]
+    pub token_count: Option<usize>,
+}
+
+/// Current `Settings` on-disk schema version. Bump this and append a new
+/// `vN_to_vN+1` migration in `settings::MIGRATIONS` whenever a field is
+/// added, renamed, or removed in a way that breaks a straight deserialize.
+pub const CURRENT_SETTINGS_VERSION: u32 = 2;
+
+fn default_settings_version() -> u32 {
+    CURRENT_SETTINGS_VERSION
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
+    /// Schema version, migrated forward by `settings::load_settings_from_disk`.
+    /// Missing on files predating this field, which `serde(default)` reads as `0`.
+    #[serde(default)]
+    pub version: u32,
     pub anthropic_api_key: String,
     pub openai_api_key: String,
     pub google_api_key: String,
@@ -36,12 +84,36 @@ pub struct Settings {
     pub perplexity_api_key: String,
     pub elevenlabs_api_key: String,
     pub default_model: String,
+    /// Key into the built-in provider registry ("anthropic", "openai",
+    /// "google", "xai", "perplexity") or the `name` of a `custom_providers`
+    /// entry. Replaces guessing the provider from the model-name prefix.
+    #[serde(default = "default_provider")]
+    pub default_provider: String,
+    #[serde(default)]
+    pub custom_providers: Vec<CustomProviderConfig>,
     pub voice_enabled: bool,
+    /// Endpoint used by `memory::search_memories_semantic`/`reindex_embeddings`
+    /// to embed memory text, OpenAI-compatible request/response shape.
+    /// Defaults to a local Ollama instance so semantic search works with no
+    /// API key configured.
+    #[serde(default = "default_embedding_api_url")]
+    pub embedding_api_url: String,
+    #[serde(default)]
+    pub embedding_api_key: String,
+}
+
+fn default_provider() -> String {
+    "anthropic".to_string()
+}
+
+fn default_embedding_api_url() -> String {
+    "http://localhost:11434/v1/embeddings".to_string()
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            version: default_settings_version(),
             anthropic_api_key: String::new(),
             openai_api_key: String::new(),
             google_api_key: String::new(),
@@ -49,11 +121,24 @@ impl Default for Settings {
             perplexity_api_key: String::new(),
             elevenlabs_api_key: String::new(),
             default_model: "claude-sonnet-4-20250514".to_string(),
+            default_provider: default_provider(),
+            custom_providers: Vec::new(),
             voice_enabled: false,
+            embedding_api_url: default_embedding_api_url(),
+            embedding_api_key: String::new(),
         }
     }
 }
 
+/// An arbitrary OpenAI-compatible endpoint (Ollama, LM Studio, OpenRouter,
+/// a self-hosted gateway, ...) registered by the user instead of baked in.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CustomProviderConfig {
+    pub name: String,
+    pub base_url: String,
+    pub api_key: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryItem {
     pub id: String,
@@ -64,6 +149,11 @@ pub struct MemoryItem {
     pub tags: Vec<String>,
     pub entities: Vec<String>,
     pub confidence: f32,
+    /// L2-normalized embedding of `title` + `content`, computed at save time
+    /// when `Settings::openai_api_key` is configured. `None` falls back to
+    /// keyword matching in `ai::build_history`.
+    #[serde(default)]
+    pub embedding: Option<Vec<f32>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,6 +189,56 @@ pub struct Skill {
     pub name: String,
     pub description: String,
     pub category: String,
+    /// Keywords that auto-activate the skill without an explicit invocation.
+    #[serde(default)]
+    pub triggers: Vec<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Other skill ids this skill expects to already be available.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default = "default_skill_enabled")]
+    pub enabled: bool,
+}
+
+fn default_skill_enabled() -> bool {
+    true
+}
+
+/// A reusable system-prompt template that seeds a session, analogous to
+/// aichat's `Role` — distinct from `Skill`, which describes a capability
+/// rather than a prompt to bind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Role {
+    pub id: String,
+    pub name: String,
+    pub prompt: String,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+}
+
+/// One immutable entry in a message's append-only edit history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageRevision {
+    pub message_id: i64,
+    pub revision: i64,
+    pub role: String,
+    pub content: String,
+    pub content_hash: String,
+    pub parent_revision: Option<i64>,
+    pub created_at: i64,
+}
+
+/// A problem found in a memory or work-item file by `memory::diagnose_storage`,
+/// reporting why `parse_markdown_memory`/`parse_work_meta` return a silent
+/// `None` for the same file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageIssue {
+    pub path: String,
+    /// One of: `missing_required_field`, `unparseable_numeric_field`,
+    /// `missing_frontmatter_delimiter`, `duplicate_id`, `unknown_memory_type`.
+    pub category: String,
+    pub message: String,
 }
 
 fn main() {
@@ -108,7 +248,14 @@ fn main() {
 
     info!("Starting PAI Desktop...");
 
-    let settings = settings::load_settings_from_disk().unwrap_or_default();
+    let loaded_settings = settings::load_settings_from_disk_versioned().unwrap_or_else(|_| settings::LoadedSettings {
+        settings: Settings::default(),
+        migrated: false,
+    });
+    if loaded_settings.migrated {
+        info!("Migrated settings.json to schema v{}", CURRENT_SETTINGS_VERSION);
+    }
+    let settings = loaded_settings.settings;
     let memories = memory::load_memories_from_disk_sync();
     let messages = messages::load_messages_from_disk_sync();
 
@@ -118,34 +265,66 @@ fn main() {
         messages.len()
     );
 
+    let completion_provider = completion::build_completion_provider(&settings);
+
     let app_state = AppState {
         settings: Mutex::new(settings),
         memories: Mutex::new(memories),
         messages: Mutex::new(messages),
+        encryption_key: Mutex::new(None),
+        usage: Mutex::new(ai::UsageStats::default()),
+        completion_provider: Mutex::new(completion_provider),
+        extraction_queue: hooks::ExtractionQueue::default(),
+        validation_queue: algorithm::ValidationQueue::default(),
+        temp_session: Mutex::new(None),
     };
 
     tauri::Builder::default()
         .manage(app_state)
         .invoke_handler(tauri::generate_handler![
             ai::chat,
+            ai::chat_stream,
             ai::get_models,
+            ai::get_usage_stats,
             settings::get_settings,
             settings::save_settings,
             skills::get_skills,
             skills::save_skill,
             skills::get_skill_content,
             skills::delete_skill,
+            skills::validate_skill,
+            roles::get_roles,
+            roles::save_role,
+            roles::get_role_content,
+            roles::delete_role,
+            roles::set_session_role,
+            permissions::capability_new,
+            permissions::permission_add,
+            permissions::permission_rm,
+            permissions::permission_ls,
+            permissions::set_session_capability,
+            permissions::check_skill_allowed,
             session::get_current_session,
             session::create_new_session,
             session::list_sessions,
             session::switch_session,
             session::delete_session,
             session::rename_session,
+            session::append_message,
+            session::get_session_messages,
+            session::clear_session_messages,
+            session::export_session,
+            session::import_session,
+            session::create_temp_session,
+            session::promote_temp_session,
             memory::get_memories,
             memory::save_memory,
             memory::load_memories_from_disk,
             memory::delete_memory,
             memory::search_memories,
+            memory::search_memories_semantic,
+            memory::reindex_embeddings,
+            memory::diagnose_storage,
             memory::save_relationship_note,
             memory::get_relationship_notes,
             memory::save_work_item,
@@ -156,7 +335,21 @@ fn main() {
             messages::get_messages,
             messages::add_message,
             messages::clear_messages,
+            messages::unlock,
+            messages::lock,
+            messages::export_messages,
+            messages::import_messages,
+            messages::search_messages,
+            messages::edit_message,
+            messages::get_message_history,
+            messages::revert_message,
         ])
+        .setup(|app| {
+            messages::spawn_message_watcher(app.handle().clone());
+            hooks::ExtractionQueue::spawn_merge_worker(app.handle().clone());
+            algorithm::ValidationQueue::spawn_merge_worker(app.handle().clone());
+            Ok(())
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -1,11 +1,15 @@
 use crate::{AppState, MemoryItem, RelationshipNote, WorkItem};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 use tauri::State;
 
 pub fn save_memory_internal(memory: &MemoryItem) -> Result<(), String> {
     ensure_dirs()?;
-    
+
     let memory_dir = match memory.memory_type.as_str() {
         "WORK" => get_work_dir(),
         "LEARNING" => get_learning_dir(),
@@ -14,22 +18,113 @@ pub fn save_memory_internal(memory: &MemoryItem) -> Result<(), String> {
     };
 
     let path = memory_dir.join(format!("{}.md", memory.id));
-    let frontmatter = format!(
-        "---\nid: {}\ntitle: {}\ntype: {}\ntags: {}\nentities: {}\nconfidence: {}\ntimestamp: {}\n---\n\n{}",
-        memory.id,
-        memory.title,
-        memory.memory_type,
-        memory.tags.join(", "),
-        memory.entities.join(", "),
-        memory.confidence,
-        memory.timestamp,
-        memory.content
-    );
-    fs::write(&path, frontmatter).map_err(|e| e.to_string())?;
-    
+    fs::write(&path, render_memory_markdown(memory)?).map_err(|e| e.to_string())?;
+
     Ok(())
 }
 
+/// The YAML frontmatter shape `render_memory_markdown`/`parse_markdown_memory`
+/// round-trip `MemoryItem` through. `tags`/`entities`/`embedding` use a
+/// tolerant deserializer so files written by the old comma-joined formatter
+/// still load.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MemoryFrontmatter {
+    id: String,
+    title: String,
+    #[serde(rename = "type", default)]
+    memory_type: String,
+    #[serde(default, deserialize_with = "deserialize_string_list")]
+    tags: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_string_list")]
+    entities: Vec<String>,
+    #[serde(default = "default_confidence")]
+    confidence: f32,
+    #[serde(default)]
+    timestamp: i64,
+    #[serde(default, deserialize_with = "deserialize_embedding")]
+    embedding: Option<Vec<f32>>,
+}
+
+fn default_confidence() -> f32 {
+    1.0
+}
+
+/// Accepts a proper YAML sequence (current format) or the legacy
+/// comma-joined string (`a, b, c`) that `tags`/`entities` used to be written as.
+fn deserialize_string_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match serde_yaml::Value::deserialize(deserializer)? {
+        serde_yaml::Value::Sequence(seq) => {
+            seq.into_iter().filter_map(|v| v.as_str().map(str::to_string)).collect()
+        }
+        serde_yaml::Value::String(s) => {
+            s.split(", ").filter(|s| !s.is_empty()).map(str::to_string).collect()
+        }
+        _ => Vec::new(),
+    })
+}
+
+/// Accepts a proper YAML sequence of numbers (current format) or the legacy
+/// comma-joined string of floats `embedding` used to be written as.
+fn deserialize_embedding<'de, D>(deserializer: D) -> Result<Option<Vec<f32>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let vector: Vec<f32> = match serde_yaml::Value::deserialize(deserializer)? {
+        serde_yaml::Value::Sequence(seq) => {
+            seq.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect()
+        }
+        serde_yaml::Value::String(s) => s.split(", ").filter_map(|s| s.parse().ok()).collect(),
+        _ => Vec::new(),
+    };
+    Ok(if vector.is_empty() { None } else { Some(vector) })
+}
+
+/// Serializes `memory`'s metadata as a proper YAML frontmatter block (quoting
+/// and escaping handled by `serde_yaml`, `tags`/`entities` emitted as YAML
+/// sequences) followed by its content verbatim.
+fn render_memory_markdown(memory: &MemoryItem) -> Result<String, String> {
+    let frontmatter = MemoryFrontmatter {
+        id: memory.id.clone(),
+        title: memory.title.clone(),
+        memory_type: memory.memory_type.clone(),
+        tags: memory.tags.clone(),
+        entities: memory.entities.clone(),
+        confidence: memory.confidence,
+        timestamp: memory.timestamp,
+        embedding: memory.embedding.clone(),
+    };
+    let yaml = serde_yaml::to_string(&frontmatter).map_err(|e| e.to_string())?;
+    Ok(format!("---\n{}---\n\n{}", yaml, memory.content))
+}
+
+/// Locates the opening `---` line and the next line that is exactly `---`,
+/// returning the text between them (the frontmatter, fed to a YAML
+/// deserializer) and everything after the closing delimiter verbatim
+/// (the body, which may itself contain `---` horizontal rules).
+fn split_memory_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let mut lines = content.split_inclusive('\n');
+    let first = lines.next()?;
+    if first.trim_end_matches(['\n', '\r']) != "---" {
+        return None;
+    }
+
+    let rest = &content[first.len()..];
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end_matches(['\n', '\r']) == "---" {
+            let frontmatter = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return Some((frontmatter, body));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
 pub fn get_base_dir() -> PathBuf {
     dirs::data_local_dir()
         .unwrap_or_else(|| PathBuf::from("."))
@@ -77,9 +172,21 @@ pub fn get_memories(state: State<'_, AppState>) -> Result<Vec<MemoryItem>, Strin
 }
 
 #[tauri::command]
-pub fn save_memory(state: State<'_, AppState>, memory: MemoryItem) -> Result<(), String> {
+pub async fn save_memory(state: State<'_, AppState>, mut memory: MemoryItem) -> Result<(), String> {
     ensure_dirs()?;
-    
+
+    let openai_api_key = {
+        let settings = state.settings.lock().map_err(|e| e.to_string())?;
+        settings.openai_api_key.clone()
+    };
+    if !openai_api_key.is_empty() {
+        let text = format!("{}\n{}", memory.title, memory.content);
+        match crate::embeddings::embed_openai(&openai_api_key, &text).await {
+            Ok(vector) => memory.embedding = Some(vector),
+            Err(e) => println!("Failed to embed memory {}: {}", memory.id, e),
+        }
+    }
+
     let memory_dir = match memory.memory_type.as_str() {
         "WORK" => get_work_dir(),
         "LEARNING" => get_learning_dir(),
@@ -88,18 +195,7 @@ pub fn save_memory(state: State<'_, AppState>, memory: MemoryItem) -> Result<(),
     };
 
     let path = memory_dir.join(format!("{}.md", memory.id));
-    let frontmatter = format!(
-        "---\nid: {}\ntitle: {}\ntype: {}\ntags: {}\nentities: {}\nconfidence: {}\ntimestamp: {}\n---\n\n{}",
-        memory.id,
-        memory.title,
-        memory.memory_type,
-        memory.tags.join(", "),
-        memory.entities.join(", "),
-        memory.confidence,
-        memory.timestamp,
-        memory.content
-    );
-    fs::write(&path, frontmatter).map_err(|e| e.to_string())?;
+    fs::write(&path, render_memory_markdown(&memory)?).map_err(|e| e.to_string())?;
 
     let mut memories = state.memories.lock().map_err(|e| e.to_string())?;
     memories.push(memory);
@@ -147,56 +243,26 @@ pub fn load_memories_from_disk(state: State<'_, AppState>) -> Result<Vec<MemoryI
 }
 
 fn parse_markdown_memory(content: &str, default_type: &str) -> Option<MemoryItem> {
-    if !content.starts_with("---") {
-        return None;
-    }
+    let (frontmatter, body) = split_memory_frontmatter(content)?;
+    let mut parsed: MemoryFrontmatter = serde_yaml::from_str(frontmatter).ok()?;
 
-    let parts: Vec<&str> = content.splitn(3, "---").collect();
-    if parts.len() < 3 {
+    if parsed.id.is_empty() || parsed.title.is_empty() {
         return None;
     }
-
-    let frontmatter = parts[1];
-    let body = parts[2].trim();
-
-    let mut id = String::new();
-    let mut title = String::new();
-    let mut memory_type = default_type.to_string();
-    let mut tags = Vec::new();
-    let mut entities = Vec::new();
-    let mut confidence = 1.0f32;
-    let mut timestamp = 0i64;
-
-    for line in frontmatter.lines() {
-        let line = line.trim();
-        if let Some((key, value)) = line.split_once(": ") {
-            let value = value.trim();
-            match key {
-                "id" => id = value.to_string(),
-                "title" => title = value.to_string(),
-                "type" => memory_type = value.to_string(),
-                "tags" => tags = value.split(", ").map(|s| s.to_string()).collect(),
-                "entities" => entities = value.split(", ").map(|s| s.to_string()).collect(),
-                "confidence" => confidence = value.parse().unwrap_or(1.0),
-                "timestamp" => timestamp = value.parse().unwrap_or(0),
-                _ => {}
-            }
-        }
-    }
-
-    if id.is_empty() || title.is_empty() {
-        return None;
+    if parsed.memory_type.is_empty() {
+        parsed.memory_type = default_type.to_string();
     }
 
     Some(MemoryItem {
-        id,
-        title,
-        content: body.to_string(),
-        memory_type,
-        timestamp,
-        tags,
-        entities,
-        confidence,
+        id: parsed.id,
+        title: parsed.title,
+        content: body.trim().to_string(),
+        memory_type: parsed.memory_type,
+        timestamp: parsed.timestamp,
+        tags: parsed.tags,
+        entities: parsed.entities,
+        confidence: parsed.confidence,
+        embedding: parsed.embedding,
     })
 }
 
@@ -218,32 +284,56 @@ pub fn delete_memory(state: State<'_, AppState>, memory_id: String) -> Result<()
     Ok(())
 }
 
+/// Minimum cosine similarity for a memory to be returned by `search_memories_semantic`.
+const SEMANTIC_SEARCH_THRESHOLD: f32 = 0.2;
+
+/// Minimum cosine similarity for two memories to be treated as the same fact.
+const NEAR_DUPLICATE_THRESHOLD: f32 = 0.92;
+
+/// Checks whether `candidate` says the same thing as something already in
+/// `existing`, so callers like `HookSystem` can skip saving the same fact
+/// twice. Compares embeddings when both sides have one, otherwise falls back
+/// to an exact (case-insensitive) title match.
+pub fn is_near_duplicate(candidate: &MemoryItem, existing: &[MemoryItem]) -> bool {
+    existing.iter().any(|m| {
+        if let (Some(a), Some(b)) = (&candidate.embedding, &m.embedding) {
+            crate::embeddings::cosine_similarity(a, b) >= NEAR_DUPLICATE_THRESHOLD
+        } else {
+            m.title.eq_ignore_ascii_case(&candidate.title)
+        }
+    })
+}
+
+/// BM25-ranked, typo-tolerant full-text search over memories, replacing the
+/// old `String::contains` substring scan. `memory_type` is a post-filter
+/// applied after ranking; `limit` caps how many results come back.
 #[tauri::command]
-pub fn search_memories(query: String, memory_type: Option<String>) -> Result<Vec<MemoryItem>, String> {
+pub fn search_memories(
+    query: String,
+    memory_type: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<MemoryItem>, String> {
     let memories = load_memories_from_disk_internal()?;
-    let query_lower = query.to_lowercase();
+    let index = crate::search::SearchIndex::build(&memories);
+    let ranked = index.search(&query);
 
-    let filtered: Vec<MemoryItem> = memories
+    let by_id: HashMap<&str, &MemoryItem> = memories.iter().map(|m| (m.id.as_str(), m)).collect();
+
+    let results = ranked
         .into_iter()
-        .filter(|m| {
-            if let Some(ref t) = memory_type {
-                if m.memory_type != *t {
-                    return false;
-                }
-            }
-            m.title.to_lowercase().contains(&query_lower)
-                || m.content.to_lowercase().contains(&query_lower)
-                || m.tags.iter().any(|t| t.to_lowercase().contains(&query_lower))
-        })
+        .filter_map(|(id, _)| by_id.get(id.as_str()).copied())
+        .filter(|m| memory_type.as_ref().map_or(true, |t| &m.memory_type == t))
+        .take(limit.unwrap_or(usize::MAX))
+        .cloned()
         .collect();
 
-    Ok(filtered)
+    Ok(results)
 }
 
-fn load_memories_from_disk_internal() -> Result<Vec<MemoryItem>, String> {
-    ensure_dirs()?;
-    let mut all_memories = Vec::new();
-
+/// Every memory `.md` path on disk, tagged with the type implied by which
+/// of the four memory dirs it lives in. Shared by every loader below so the
+/// dir layout is only enumerated in one place.
+fn collect_markdown_files() -> Vec<(PathBuf, &'static str)> {
     let dirs = [
         (get_work_dir(), "WORK"),
         (get_learning_dir(), "LEARNING"),
@@ -251,24 +341,40 @@ fn load_memories_from_disk_internal() -> Result<Vec<MemoryItem>, String> {
         (get_memory_dir(), "general"),
     ];
 
+    let mut files = Vec::new();
     for (dir, mem_type) in dirs {
         if !dir.exists() {
             continue;
         }
-        
+
         if let Ok(entries) = fs::read_dir(&dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if path.extension().map_or(false, |ext| ext == "md") {
-                    if let Ok(content) = fs::read_to_string(&path) {
-                        if let Some(memory) = parse_markdown_memory(&content, mem_type) {
-                            all_memories.push(memory);
-                        }
-                    }
+                    files.push((path, mem_type));
                 }
             }
         }
     }
+    files
+}
+
+/// Backed by a docket + packed-data cache (`cache::DiskCache`) keyed on each
+/// file's mtime/size, so a reload only re-parses markdown that actually
+/// changed since the last call instead of rescanning the whole corpus.
+fn load_memories_from_disk_internal() -> Result<Vec<MemoryItem>, String> {
+    ensure_dirs()?;
+
+    let discovered = collect_markdown_files();
+    let type_by_path: HashMap<PathBuf, &'static str> = discovered.iter().cloned().collect();
+    let paths: Vec<PathBuf> = discovered.into_iter().map(|(path, _)| path).collect();
+
+    let cache = crate::cache::DiskCache::new("memories");
+    let mut all_memories = cache.load(&get_base_dir(), &paths, |path| {
+        let mem_type = type_by_path.get(path).copied().unwrap_or("general");
+        let content = fs::read_to_string(path).ok()?;
+        parse_markdown_memory(&content, mem_type)
+    });
 
     all_memories.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
     Ok(all_memories)
@@ -389,30 +495,34 @@ pub fn save_work_item(work: WorkItem) -> Result<(), String> {
     Ok(())
 }
 
+/// Backed by the same `cache::DiskCache` docket scheme as
+/// `load_memories_from_disk_internal`, keyed on each `META.yaml`'s mtime/size.
 #[tauri::command]
 pub fn get_work_items() -> Result<Vec<WorkItem>, String> {
     ensure_dirs()?;
     let work_dir = get_work_dir();
-    let mut items = Vec::new();
 
     if !work_dir.exists() {
-        return Ok(items);
+        return Ok(Vec::new());
     }
 
+    let mut meta_paths = Vec::new();
     if let Ok(entries) = fs::read_dir(&work_dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_dir() {
-                let meta_path = path.join("META.yaml");
-                if let Ok(content) = fs::read_to_string(&meta_path) {
-                    if let Some(work) = parse_work_meta(&content, path.file_name().unwrap().to_str().unwrap()) {
-                        items.push(work);
-                    }
-                }
+                meta_paths.push(path.join("META.yaml"));
             }
         }
     }
 
+    let cache = crate::cache::DiskCache::new("work_items");
+    let mut items: Vec<WorkItem> = cache.load(&get_base_dir(), &meta_paths, |meta_path| {
+        let content = fs::read_to_string(meta_path).ok()?;
+        let id = meta_path.parent()?.file_name()?.to_str()?;
+        parse_work_meta(&content, id)
+    });
+
     items.sort_by(|a, b| b.created_at.cmp(&a.created_at));
     Ok(items)
 }
@@ -516,3 +626,298 @@ pub fn get_prds() -> Result<Vec<(String, String)>, String> {
 pub fn load_memories_from_disk_sync() -> Vec<MemoryItem> {
     load_memories_from_disk_internal().unwrap_or_default()
 }
+
+/// Model requested from `Settings::embedding_api_url`. Ignored by providers
+/// that pin their own (e.g. a single-model Ollama pull) but required by the
+/// OpenAI-compatible request shape `embeddings::embed` sends.
+const EMBEDDING_MODEL: &str = "nomic-embed-text";
+
+/// On-disk cache of a memory's embedding, stored next to its `.md` file as
+/// `{id}.vec` instead of inline in the frontmatter, so re-embedding can be
+/// skipped whenever `content_hash` still matches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EmbeddingSidecar {
+    content_hash: String,
+    vector: Vec<f32>,
+}
+
+fn sidecar_path_for(md_path: &Path) -> PathBuf {
+    md_path.with_extension("vec")
+}
+
+/// Hash of the text `search_memories_semantic`/`reindex_embeddings` embed,
+/// so a sidecar can be recognized as stale once a memory is edited.
+fn content_hash(memory: &MemoryItem) -> String {
+    let mut hasher = DefaultHasher::new();
+    memory.title.hash(&mut hasher);
+    memory.content.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+fn load_sidecar(md_path: &Path) -> Option<EmbeddingSidecar> {
+    let content = fs::read_to_string(sidecar_path_for(md_path)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_sidecar(md_path: &Path, sidecar: &EmbeddingSidecar) -> Result<(), String> {
+    let json = serde_json::to_string(sidecar).map_err(|e| e.to_string())?;
+    fs::write(sidecar_path_for(md_path), json).map_err(|e| e.to_string())
+}
+
+/// All memory `.md` files on disk alongside the path `reindex_embeddings`
+/// and `search_memories_semantic` use to find/write their `.vec` sidecar.
+fn list_memory_files() -> Vec<(PathBuf, MemoryItem)> {
+    collect_markdown_files()
+        .into_iter()
+        .filter_map(|(path, mem_type)| {
+            let content = fs::read_to_string(&path).ok()?;
+            let memory = parse_markdown_memory(&content, mem_type)?;
+            Some((path, memory))
+        })
+        .collect()
+}
+
+/// Walks all four memory dirs and (re)generates any `.vec` sidecar whose
+/// `content_hash` is missing or stale, embedding via `Settings::embedding_api_url`.
+/// Returns how many sidecars were (re)written.
+#[tauri::command]
+pub async fn reindex_embeddings(state: State<'_, AppState>) -> Result<usize, String> {
+    let (api_url, api_key) = {
+        let settings = state.settings.lock().map_err(|e| e.to_string())?;
+        (settings.embedding_api_url.clone(), settings.embedding_api_key.clone())
+    };
+
+    let mut reindexed = 0;
+    for (path, memory) in list_memory_files() {
+        let hash = content_hash(&memory);
+        let is_current = load_sidecar(&path).map_or(false, |s| s.content_hash == hash);
+        if is_current {
+            continue;
+        }
+
+        let text = format!("{}\n{}", memory.title, memory.content);
+        match crate::embeddings::embed(&api_url, &api_key, EMBEDDING_MODEL, &text).await {
+            Ok(vector) => {
+                save_sidecar(&path, &EmbeddingSidecar { content_hash: hash, vector })?;
+                reindexed += 1;
+            }
+            Err(e) => println!("Failed to embed memory {}: {}", memory.id, e),
+        }
+    }
+
+    Ok(reindexed)
+}
+
+/// Semantic search over memories using the sidecar `.vec` embeddings built
+/// by `reindex_embeddings`, parallel to the keyword-ranked `search_memories`.
+/// Memories without a current sidecar are skipped rather than embedded
+/// inline; run `reindex_embeddings` first to cover them. Supersedes the
+/// earlier `semantic_search_memories` command, which embedded inline via
+/// `openai_api_key` only and duplicated this one end-to-end; this command
+/// covers the same need through the configurable `embedding_api_url`
+/// instead, so that one was removed rather than shipping both.
+#[tauri::command]
+pub async fn search_memories_semantic(
+    state: State<'_, AppState>,
+    query: String,
+    top_k: usize,
+    memory_type: Option<String>,
+) -> Result<Vec<MemoryItem>, String> {
+    let (api_url, api_key) = {
+        let settings = state.settings.lock().map_err(|e| e.to_string())?;
+        (settings.embedding_api_url.clone(), settings.embedding_api_key.clone())
+    };
+
+    let query_vector = crate::embeddings::embed(&api_url, &api_key, EMBEDDING_MODEL, &query).await?;
+
+    let mut scored: Vec<(MemoryItem, f32)> = list_memory_files()
+        .into_iter()
+        .filter(|(_, m)| memory_type.as_ref().map_or(true, |t| &m.memory_type == t))
+        .filter_map(|(path, memory)| {
+            let hash = content_hash(&memory);
+            let sidecar = load_sidecar(&path).filter(|s| s.content_hash == hash)?;
+            let score = crate::embeddings::cosine_similarity(&query_vector, &sidecar.vector);
+            Some((memory, score))
+        })
+        .filter(|(_, score)| *score >= SEMANTIC_SEARCH_THRESHOLD)
+        .collect();
+
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(scored.into_iter().take(top_k).map(|(m, _)| m).collect())
+}
+
+const KNOWN_MEMORY_TYPES: &[&str] = &["WORK", "LEARNING", "RELATIONSHIP", "general"];
+
+/// Re-parses a memory `.md` file the way `parse_markdown_memory` does, but
+/// collects every problem instead of giving up and returning `None` at the
+/// first one. Returns the issues found plus the file's `id`, if it has one,
+/// so `diagnose_storage` can also check for duplicates across files.
+fn diagnose_memory_file(content: &str) -> (Vec<(String, String)>, Option<String>) {
+    let Some((frontmatter, _body)) = split_memory_frontmatter(content) else {
+        return (
+            vec![(
+                "missing_frontmatter_delimiter".to_string(),
+                "File is missing its opening or closing `---` frontmatter delimiter".to_string(),
+            )],
+            None,
+        );
+    };
+
+    let Ok(serde_yaml::Value::Mapping(fields)) = serde_yaml::from_str(frontmatter) else {
+        return (
+            vec![(
+                "missing_required_field".to_string(),
+                "Frontmatter is not a valid YAML mapping".to_string(),
+            )],
+            None,
+        );
+    };
+
+    let field = |key: &str| -> Option<&serde_yaml::Value> {
+        fields.iter().find(|(k, _)| k.as_str() == Some(key)).map(|(_, v)| v)
+    };
+    let field_str = |key: &str| field(key).and_then(|v| v.as_str()).unwrap_or("");
+    let id = field_str("id");
+    let title = field_str("title");
+    let memory_type = field("type").and_then(|v| v.as_str());
+
+    let mut missing_required = Vec::new();
+    if id.is_empty() {
+        missing_required.push("id");
+    }
+    if title.is_empty() {
+        missing_required.push("title");
+    }
+
+    let mut bad_numeric = Vec::new();
+    if let Some(value) = field("confidence") {
+        if value.as_f64().is_none() {
+            bad_numeric.push("confidence");
+        }
+    }
+    if let Some(value) = field("timestamp") {
+        if value.as_i64().is_none() {
+            bad_numeric.push("timestamp");
+        }
+    }
+
+    let mut issues = Vec::new();
+    if !missing_required.is_empty() {
+        issues.push((
+            "missing_required_field".to_string(),
+            format!("Missing required fields: {}", missing_required.join(", ")),
+        ));
+    }
+    if !bad_numeric.is_empty() {
+        issues.push((
+            "unparseable_numeric_field".to_string(),
+            format!("Unparseable numeric fields: {}", bad_numeric.join(", ")),
+        ));
+    }
+    if let Some(memory_type) = memory_type {
+        if !KNOWN_MEMORY_TYPES.contains(&memory_type) {
+            issues.push((
+                "unknown_memory_type".to_string(),
+                format!("Unknown memory_type '{}'", memory_type),
+            ));
+        }
+    }
+
+    (issues, if id.is_empty() { None } else { Some(id.to_string()) })
+}
+
+/// Same idea as `diagnose_memory_file`, for a work item's `META.yaml`.
+fn diagnose_work_meta(content: &str) -> Vec<(String, String)> {
+    let mut title = String::new();
+    let mut bad_numeric = Vec::new();
+
+    for line in content.lines() {
+        if let Some((key, value)) = line.split_once(": ") {
+            let value = value.trim();
+            match key {
+                "title" => title = value.to_string(),
+                "created_at" if value.parse::<i64>().is_err() => bad_numeric.push("created_at"),
+                "completed_at" if !value.is_empty() && value.parse::<i64>().is_err() => {
+                    bad_numeric.push("completed_at")
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut issues = Vec::new();
+    if title.is_empty() {
+        issues.push((
+            "missing_required_field".to_string(),
+            "Missing required fields: title".to_string(),
+        ));
+    }
+    if !bad_numeric.is_empty() {
+        issues.push((
+            "unparseable_numeric_field".to_string(),
+            format!("Unparseable numeric fields: {}", bad_numeric.join(", ")),
+        ));
+    }
+    issues
+}
+
+/// Walks every memory dir, the relationship dir, and the work dir, reporting
+/// exactly which required fields are missing or malformed per file instead
+/// of letting `parse_markdown_memory`/`parse_work_meta` silently drop the
+/// file from the UI. Memory ids are also checked for collisions across
+/// files, since `load_memories_from_disk_internal` loads each such file as
+/// a separate memory carrying the same id.
+#[tauri::command]
+pub fn diagnose_storage() -> Result<Vec<crate::StorageIssue>, String> {
+    ensure_dirs()?;
+    let mut issues = Vec::new();
+    let mut ids_seen: HashMap<String, Vec<String>> = HashMap::new();
+
+    for (path, _mem_type) in collect_markdown_files() {
+        let Ok(content) = fs::read_to_string(&path) else { continue };
+        let path = path.display().to_string();
+
+        let (file_issues, id) = diagnose_memory_file(&content);
+        for (category, message) in file_issues {
+            issues.push(crate::StorageIssue { path: path.clone(), category, message });
+        }
+        if let Some(id) = id {
+            ids_seen.entry(id).or_default().push(path);
+        }
+    }
+
+    for (id, paths) in &ids_seen {
+        if paths.len() < 2 {
+            continue;
+        }
+        for path in paths {
+            let others: Vec<&str> =
+                paths.iter().filter(|p| *p != path).map(|p| p.as_str()).collect();
+            issues.push(crate::StorageIssue {
+                path: path.clone(),
+                category: "duplicate_id".to_string(),
+                message: format!("Duplicate id '{}' also used by {}", id, others.join(", ")),
+            });
+        }
+    }
+
+    let work_dir = get_work_dir();
+    if work_dir.exists() {
+        if let Ok(entries) = fs::read_dir(&work_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if !path.is_dir() {
+                    continue;
+                }
+                let meta_path = path.join("META.yaml");
+                let Ok(content) = fs::read_to_string(&meta_path) else { continue };
+                let path = meta_path.display().to_string();
+                for (category, message) in diagnose_work_meta(&content) {
+                    issues.push(crate::StorageIssue { path: path.clone(), category, message });
+                }
+            }
+        }
+    }
+
+    Ok(issues)
+}
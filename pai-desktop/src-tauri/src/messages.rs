@@ -1,7 +1,18 @@
-use crate::{AppState, ChatMessage};
+use crate::crypto::{self, KEY_LEN};
+use crate::{AppState, ChatMessage, MessageRevision};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::{params, Connection};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
-use tauri::State;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 pub fn get_messages_dir() -> PathBuf {
     dirs::data_local_dir()
@@ -10,20 +21,226 @@ pub fn get_messages_dir() -> PathBuf {
         .join("messages")
 }
 
+pub fn get_db_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("PAI")
+        .join("pai.db")
+}
+
+fn open_db() -> Result<Connection, String> {
+    let path = get_db_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let conn = Connection::open(&path).map_err(|e| e.to_string())?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS messages (
+            timestamp INTEGER PRIMARY KEY,
+            role TEXT,
+            content TEXT,
+            conversation_id TEXT,
+            nonce BLOB,
+            ciphertext BLOB
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(content, timestamp UNINDEXED)",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS message_revisions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            message_id INTEGER NOT NULL,
+            revision INTEGER NOT NULL,
+            role TEXT,
+            content TEXT,
+            content_hash TEXT NOT NULL,
+            parent_revision INTEGER,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
+
+    migrate_json_files_if_needed(&conn)?;
+    backfill_fts_if_needed(&conn)?;
+
+    Ok(conn)
+}
+
+/// Only plaintext messages can be indexed — encrypted content is opaque
+/// ciphertext and is never added to the index, even after an unlock.
+fn backfill_fts_if_needed(conn: &Connection) -> Result<(), String> {
+    let fts_count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM messages_fts", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if fts_count > 0 {
+        return Ok(());
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT timestamp, content FROM messages WHERE content IS NOT NULL")
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<(i64, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    for (timestamp, content) in rows {
+        conn.execute(
+            "INSERT INTO messages_fts (content, timestamp) VALUES (?1, ?2)",
+            params![content, timestamp],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// One-time migration for installs that still have the legacy file-per-message
+/// layout in `PAI/messages`; runs only while the table is empty.
+fn migrate_json_files_if_needed(conn: &Connection) -> Result<(), String> {
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM messages", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if count > 0 {
+        return Ok(());
+    }
+
+    let messages_dir = get_messages_dir();
+    if !messages_dir.exists() {
+        return Ok(());
+    }
+
+    if let Ok(entries) = fs::read_dir(&messages_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map_or(false, |ext| ext == "json") {
+                if let Ok(content) = fs::read_to_string(&path) {
+                    if let Ok(message) = serde_json::from_str::<ChatMessage>(&content) {
+                        conn.execute(
+                            "INSERT OR IGNORE INTO messages (timestamp, role, content, conversation_id) VALUES (?1, ?2, ?3, ?4)",
+                            params![message.timestamp, message.role, message.content, message.conversation_id],
+                        )
+                        .map_err(|e| e.to_string())?;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+struct StoredRow {
+    timestamp: i64,
+    role: Option<String>,
+    content: Option<String>,
+    conversation_id: Option<String>,
+    nonce: Option<Vec<u8>>,
+    ciphertext: Option<Vec<u8>>,
+}
+
+fn row_to_stored(row: &rusqlite::Row) -> rusqlite::Result<StoredRow> {
+    Ok(StoredRow {
+        timestamp: row.get(0)?,
+        role: row.get(1)?,
+        content: row.get(2)?,
+        conversation_id: row.get(3)?,
+        nonce: row.get(4)?,
+        ciphertext: row.get(5)?,
+    })
+}
+
+/// Decrypts an encrypted row into a `ChatMessage`, or returns `None` if it's
+/// encrypted and either locked or fails AEAD authentication.
+fn decode_row(row: StoredRow, key: Option<&[u8; KEY_LEN]>) -> Option<ChatMessage> {
+    match (row.nonce, row.ciphertext) {
+        (Some(nonce), Some(ciphertext)) => {
+            let key = key?;
+            let plaintext = crypto::decrypt(key, &nonce, &ciphertext).ok()?;
+            serde_json::from_slice::<ChatMessage>(&plaintext).ok()
+        }
+        _ => Some(ChatMessage {
+            role: row.role?,
+            content: row.content?,
+            timestamp: row.timestamp,
+            conversation_id: row.conversation_id,
+            usage: None,
+            token_count: None,
+        }),
+    }
+}
+
 #[tauri::command]
 pub fn get_messages(state: State<'_, AppState>) -> Result<Vec<ChatMessage>, String> {
     let messages = state.messages.lock().map_err(|e| e.to_string())?;
     Ok(messages.clone())
 }
 
+/// `timestamp` is the `messages` table's primary key, but it's only
+/// second-resolution (`chrono::Utc::now().timestamp()`) — trivially
+/// reachable for two messages (e.g. a user turn and its assistant reply)
+/// to land in the same second. Nudges `timestamp` forward one tick at a
+/// time until it's free, so a collision can't throw a UNIQUE constraint
+/// error and silently drop the message from persistence.
+fn reserve_unique_timestamp(conn: &Connection, timestamp: i64) -> Result<i64, String> {
+    let mut candidate = timestamp;
+    loop {
+        let taken: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM messages WHERE timestamp = ?1",
+                params![candidate],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if taken == 0 {
+            return Ok(candidate);
+        }
+        candidate += 1;
+    }
+}
+
 #[tauri::command]
-pub fn add_message(state: State<'_, AppState>, message: ChatMessage) -> Result<(), String> {
-    let messages_dir = get_messages_dir();
-    fs::create_dir_all(&messages_dir).map_err(|e| e.to_string())?;
+pub fn add_message(state: State<'_, AppState>, mut message: ChatMessage) -> Result<(), String> {
+    let conn = open_db()?;
+    let key = state.encryption_key.lock().map_err(|e| e.to_string())?.clone();
+
+    message.timestamp = reserve_unique_timestamp(&conn, message.timestamp)?;
 
-    let path = messages_dir.join(format!("{}.json", message.timestamp));
-    let json = serde_json::to_string_pretty(&message).map_err(|e| e.to_string())?;
-    fs::write(&path, json).map_err(|e| e.to_string())?;
+    match key {
+        Some(key) => {
+            let plaintext = serde_json::to_vec(&message).map_err(|e| e.to_string())?;
+            let blob = crypto::encrypt(&key, &plaintext)?;
+            conn.execute(
+                "INSERT INTO messages (timestamp, nonce, ciphertext) VALUES (?1, ?2, ?3)",
+                params![message.timestamp, blob.nonce, blob.ciphertext],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO messages (timestamp, role, content, conversation_id) VALUES (?1, ?2, ?3, ?4)",
+                params![message.timestamp, message.role, message.content, message.conversation_id],
+            )
+            .map_err(|e| e.to_string())?;
+            conn.execute(
+                "INSERT INTO messages_fts (content, timestamp) VALUES (?1, ?2)",
+                params![message.content, message.timestamp],
+            )
+            .map_err(|e| e.to_string())?;
+
+            record_revision(&conn, message.timestamp, &message.role, &message.content, None)?;
+        }
+    }
 
     let mut messages = state.messages.lock().map_err(|e| e.to_string())?;
     messages.push(message);
@@ -31,43 +248,422 @@ pub fn add_message(state: State<'_, AppState>, message: ChatMessage) -> Result<(
     Ok(())
 }
 
+fn content_hash(content: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+/// Appends a new immutable revision for `message_id`. Revision numbers start
+/// at 1 and increase monotonically per message.
+fn record_revision(
+    conn: &Connection,
+    message_id: i64,
+    role: &str,
+    content: &str,
+    parent_revision: Option<i64>,
+) -> Result<i64, String> {
+    let next_revision: i64 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(revision), 0) + 1 FROM message_revisions WHERE message_id = ?1",
+            params![message_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "INSERT INTO message_revisions (message_id, revision, role, content, content_hash, parent_revision, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            message_id,
+            next_revision,
+            role,
+            content,
+            content_hash(content),
+            parent_revision,
+            chrono::Utc::now().timestamp(),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(next_revision)
+}
+
+/// Appends a new revision for an existing message and materializes it into
+/// the `messages`/`messages_fts` tables so `get_messages` reflects the edit.
+/// Only plaintext (unencrypted) messages can be edited this way.
 #[tauri::command]
-pub fn clear_messages(state: State<'_, AppState>) -> Result<(), String> {
-    let messages_dir = get_messages_dir();
-    
-    if messages_dir.exists() {
-        fs::remove_dir_all(&messages_dir).map_err(|e| e.to_string())?;
-        fs::create_dir_all(&messages_dir).map_err(|e| e.to_string())?;
+pub fn edit_message(state: State<'_, AppState>, message_id: i64, new_content: String) -> Result<(), String> {
+    let conn = open_db()?;
+
+    let role: String = conn
+        .query_row("SELECT role FROM messages WHERE timestamp = ?1", params![message_id], |row| row.get(0))
+        .map_err(|_| format!("Message {} not found or is encrypted", message_id))?;
+
+    let latest_revision: i64 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(revision), 0) FROM message_revisions WHERE message_id = ?1",
+            params![message_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let parent = if latest_revision > 0 { Some(latest_revision) } else { None };
+
+    record_revision(&conn, message_id, &role, &new_content, parent)?;
+    materialize_revision(&conn, message_id, &role, &new_content)?;
+
+    let mut messages = state.messages.lock().map_err(|e| e.to_string())?;
+    if let Some(m) = messages.iter_mut().find(|m| m.timestamp == message_id) {
+        m.content = new_content;
     }
 
+    Ok(())
+}
+
+fn materialize_revision(conn: &Connection, message_id: i64, role: &str, content: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE messages SET role = ?1, content = ?2 WHERE timestamp = ?3",
+        params![role, content, message_id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE messages_fts SET content = ?1 WHERE timestamp = ?2",
+        params![content, message_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Returns every revision recorded for `message_id`, oldest first.
+#[tauri::command]
+pub fn get_message_history(message_id: i64) -> Result<Vec<MessageRevision>, String> {
+    let conn = open_db()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT message_id, revision, role, content, content_hash, parent_revision, created_at
+             FROM message_revisions WHERE message_id = ?1 ORDER BY revision",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let revisions = stmt
+        .query_map(params![message_id], |row| {
+            Ok(MessageRevision {
+                message_id: row.get(0)?,
+                revision: row.get(1)?,
+                role: row.get(2)?,
+                content: row.get(3)?,
+                content_hash: row.get(4)?,
+                parent_revision: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(revisions)
+}
+
+/// Reverts `message_id` to an earlier `revision` by appending a *new*
+/// revision with that old content, rather than mutating history in place.
+#[tauri::command]
+pub fn revert_message(state: State<'_, AppState>, message_id: i64, revision: i64) -> Result<(), String> {
+    let conn = open_db()?;
+
+    let (role, content): (String, String) = conn
+        .query_row(
+            "SELECT role, content FROM message_revisions WHERE message_id = ?1 AND revision = ?2",
+            params![message_id, revision],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| format!("Revision {} of message {} not found", revision, message_id))?;
+
+    let latest_revision: i64 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(revision), 0) FROM message_revisions WHERE message_id = ?1",
+            params![message_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    record_revision(&conn, message_id, &role, &content, Some(latest_revision))?;
+    materialize_revision(&conn, message_id, &role, &content)?;
+
+    let mut messages = state.messages.lock().map_err(|e| e.to_string())?;
+    if let Some(m) = messages.iter_mut().find(|m| m.timestamp == message_id) {
+        m.content = content;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clear_messages(state: State<'_, AppState>) -> Result<(), String> {
+    let conn = open_db()?;
+    conn.execute("DELETE FROM messages", [])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM messages_fts", [])
+        .map_err(|e| e.to_string())?;
+
     let mut messages = state.messages.lock().map_err(|e| e.to_string())?;
     messages.clear();
 
     Ok(())
 }
 
+/// Quotes a user query as a single FTS5 string literal so punctuation like
+/// `-`, `+`, `AND`/`OR`, or an unterminated `"` is treated as literal text
+/// instead of being parsed as FTS5 query syntax (which would otherwise raise
+/// a hard syntax error for ordinary search input).
+fn fts5_quote(query: &str) -> String {
+    format!("\"{}\"", query.replace('"', "\"\""))
+}
+
+/// Ranks matches by BM25 (SQLite FTS5's default rank) and returns the most
+/// relevant messages first. Only plaintext messages are indexed.
+#[tauri::command]
+pub fn search_messages(query: String) -> Result<Vec<ChatMessage>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.timestamp, m.role, m.content, m.conversation_id
+             FROM messages_fts f
+             JOIN messages m ON m.timestamp = f.timestamp
+             WHERE f.content MATCH ?1
+             ORDER BY rank",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let messages = stmt
+        .query_map(params![fts5_quote(&query)], |row| {
+            Ok(ChatMessage {
+                timestamp: row.get(0)?,
+                role: row.get(1)?,
+                content: row.get(2)?,
+                conversation_id: row.get(3)?,
+                usage: None,
+                token_count: None,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(messages)
+}
+
+/// Derives the encryption key from `passphrase` (creating the keyfile on first
+/// use) and loads it into `AppState` so subsequent calls decrypt transparently.
+#[tauri::command]
+pub fn unlock(state: State<'_, AppState>, passphrase: String) -> Result<(), String> {
+    let key = crypto::unlock_with_passphrase(&passphrase)?;
+
+    {
+        let mut encryption_key = state.encryption_key.lock().map_err(|e| e.to_string())?;
+        *encryption_key = Some(key);
+    }
+
+    let messages = load_messages_internal(&state)?;
+    let mut state_messages = state.messages.lock().map_err(|e| e.to_string())?;
+    *state_messages = messages;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn lock(state: State<'_, AppState>) -> Result<(), String> {
+    let mut encryption_key = state.encryption_key.lock().map_err(|e| e.to_string())?;
+    *encryption_key = None;
+    Ok(())
+}
+
 pub fn load_messages_from_disk_sync() -> Vec<ChatMessage> {
-    let messages_dir = get_messages_dir();
-    
-    if !messages_dir.exists() {
-        return Vec::new();
+    load_messages_internal_unkeyed().unwrap_or_default()
+}
+
+fn load_messages_internal_unkeyed() -> Result<Vec<ChatMessage>, String> {
+    let conn = open_db()?;
+    query_all_messages(&conn, None)
+}
+
+fn load_messages_internal(state: &State<'_, AppState>) -> Result<Vec<ChatMessage>, String> {
+    let conn = open_db()?;
+    let key = state.encryption_key.lock().map_err(|e| e.to_string())?.clone();
+    query_all_messages(&conn, key.as_ref())
+}
+
+fn query_all_messages(conn: &Connection, key: Option<&[u8; KEY_LEN]>) -> Result<Vec<ChatMessage>, String> {
+    let mut stmt = conn
+        .prepare("SELECT timestamp, role, content, conversation_id, nonce, ciphertext FROM messages ORDER BY timestamp")
+        .map_err(|e| e.to_string())?;
+
+    let messages = stmt
+        .query_map([], row_to_stored)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|row| decode_row(row, key))
+        .collect();
+
+    Ok(messages)
+}
+
+/// Packages `pai.db` and the `keyfile` (if present) into a single gzipped
+/// tarball so users can back up or move their history between machines.
+#[tauri::command]
+pub fn export_messages(path: String) -> Result<(), String> {
+    let out_file = fs::File::create(&path).map_err(|e| e.to_string())?;
+    let encoder = GzEncoder::new(out_file, Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+
+    let db_path = get_db_path();
+    if db_path.exists() {
+        builder
+            .append_path_with_name(&db_path, "pai.db")
+            .map_err(|e| e.to_string())?;
     }
 
-    let mut messages = Vec::new();
-    
-    if let Ok(entries) = fs::read_dir(&messages_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().map_or(false, |ext| ext == "json") {
-                if let Ok(content) = fs::read_to_string(&path) {
-                    if let Ok(message) = serde_json::from_str::<ChatMessage>(&content) {
-                        messages.push(message);
-                    }
-                }
+    let keyfile_path = crypto::get_keyfile_path();
+    if keyfile_path.exists() {
+        builder
+            .append_path_with_name(&keyfile_path, "keyfile")
+            .map_err(|e| e.to_string())?;
+    }
+
+    builder.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Restores a tarball written by `export_messages`, merging rows into the
+/// current database by `timestamp` so re-importing is idempotent.
+#[tauri::command]
+pub fn import_messages(state: State<'_, AppState>, path: String) -> Result<(), String> {
+    let extract_dir = std::env::temp_dir().join(format!("pai-import-{}", chrono::Utc::now().timestamp_millis()));
+    fs::create_dir_all(&extract_dir).map_err(|e| e.to_string())?;
+
+    let in_file = fs::File::open(&path).map_err(|e| e.to_string())?;
+    let decoder = GzDecoder::new(in_file);
+    let mut archive = tar::Archive::new(decoder);
+
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        // `unpack_in` rejects both `..` and absolute entry paths, unlike a
+        // manual `ParentDir` check combined with `unpack`.
+        entry.unpack_in(&extract_dir).map_err(|e| e.to_string())?;
+    }
+
+    let imported_db = extract_dir.join("pai.db");
+    if imported_db.exists() {
+        merge_db(&imported_db)?;
+    }
+
+    let imported_keyfile = extract_dir.join("keyfile");
+    let keyfile_path = crypto::get_keyfile_path();
+    if imported_keyfile.exists() && !keyfile_path.exists() {
+        fs::copy(&imported_keyfile, &keyfile_path).map_err(|e| e.to_string())?;
+    }
+
+    fs::remove_dir_all(&extract_dir).ok();
+
+    let messages = load_messages_internal(&state)?;
+    let mut state_messages = state.messages.lock().map_err(|e| e.to_string())?;
+    *state_messages = messages;
+
+    Ok(())
+}
+
+fn merge_db(imported_db: &Path) -> Result<(), String> {
+    let imported_conn = Connection::open(imported_db).map_err(|e| e.to_string())?;
+    let mut stmt = imported_conn
+        .prepare("SELECT timestamp, role, content, conversation_id, nonce, ciphertext FROM messages")
+        .map_err(|e| e.to_string())?;
+    let rows: Vec<StoredRow> = stmt
+        .query_map([], row_to_stored)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let conn = open_db()?;
+    for row in rows {
+        conn.execute(
+            "INSERT OR IGNORE INTO messages (timestamp, role, content, conversation_id, nonce, ciphertext) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![row.timestamp, row.role, row.content, row.conversation_id, row.nonce, row.ciphertext],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Watches `pai.db` for external changes (another process, a sync tool, a
+/// manual edit) and reconciles `AppState.messages` so the app never runs
+/// stale relative to what's on disk. Runs for the lifetime of the app.
+///
+/// `ai::chat_internal` pushes each chat turn straight into `AppState.messages`
+/// without ever calling `add_message`, so those turns never reach `pai.db`.
+/// A reload triggered by this watcher must not clobber them: it merges the
+/// freshly loaded rows with whatever's already in memory and isn't in the
+/// DB, rather than replacing the vector outright.
+pub fn spawn_message_watcher(app_handle: AppHandle) {
+    std::thread::spawn(move || {
+        let (tx, rx) = channel();
+        let mut watcher: RecommendedWatcher = match notify::recommended_watcher(tx) {
+            Ok(w) => w,
+            Err(e) => {
+                log::warn!("Failed to start message watcher: {}", e);
+                return;
             }
+        };
+
+        let db_path = get_db_path();
+        let watch_dir = match db_path.parent() {
+            Some(dir) => dir.to_path_buf(),
+            None => return,
+        };
+        if let Err(e) = watcher.watch(&watch_dir, RecursiveMode::NonRecursive) {
+            log::warn!("Failed to watch {:?}: {}", watch_dir, e);
+            return;
         }
-    }
 
-    messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-    messages
+        let debounce = Duration::from_millis(500);
+        let mut last_reload = Instant::now() - debounce;
+
+        for res in rx {
+            let event = match res {
+                Ok(event) => event,
+                Err(e) => {
+                    log::warn!("Message watcher error: {}", e);
+                    continue;
+                }
+            };
+
+            let touches_db = event.paths.iter().any(|p| p == &db_path);
+            let is_relevant = matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_));
+            if !touches_db || !is_relevant || last_reload.elapsed() < debounce {
+                continue;
+            }
+            last_reload = Instant::now();
+
+            let state = app_handle.state::<AppState>();
+            let db_messages = match load_messages_internal(&state) {
+                Ok(m) => m,
+                Err(e) => {
+                    log::warn!("Failed to reload messages after external change: {}", e);
+                    continue;
+                }
+            };
+
+            if let Ok(mut guard) = state.messages.lock() {
+                let db_timestamps: HashSet<i64> = db_messages.iter().map(|m| m.timestamp).collect();
+                let unpersisted: Vec<ChatMessage> =
+                    guard.iter().filter(|m| !db_timestamps.contains(&m.timestamp)).cloned().collect();
+
+                let mut merged = db_messages;
+                merged.extend(unpersisted);
+                merged.sort_by_key(|m| m.timestamp);
+                *guard = merged;
+            }
+            let _ = app_handle.emit("messages-updated", ());
+        }
+    });
 }
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// An auditable allow-list, inspired by Tauri's ACL permission/capability
+/// model: a capability grants a set of otherwise-restricted skills and
+/// commands, and a session is only as permissive as whichever capability
+/// it's bound to.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Capability {
+    pub identifier: String,
+    pub allowed_skills: Vec<String>,
+    pub allowed_commands: Vec<String>,
+}
+
+/// Skill ids denied by default unless an active capability explicitly
+/// grants them. Named explicitly rather than derived from `Skill::category`
+/// because risk doesn't line up with category ("browser" and "apify" are
+/// filed under "tools", not "security").
+const RESTRICTED_SKILL_IDS: &[&str] = &["redteam", "recon", "osint", "browser", "apify"];
+
+pub fn get_acl_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("PAI")
+        .join("acl")
+}
+
+pub fn get_capabilities_dir() -> PathBuf {
+    get_acl_dir().join("capabilities")
+}
+
+fn get_sessions_dir() -> PathBuf {
+    get_acl_dir().join("sessions")
+}
+
+fn ensure_dirs() -> Result<(), String> {
+    fs::create_dir_all(get_capabilities_dir()).map_err(|e| e.to_string())?;
+    fs::create_dir_all(get_sessions_dir()).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn capability_path(identifier: &str) -> PathBuf {
+    get_capabilities_dir().join(format!("{}.json", identifier))
+}
+
+fn load_capability(identifier: &str) -> Result<Capability, String> {
+    let path = capability_path(identifier);
+    if !path.exists() {
+        return Err(format!("Capability '{}' not found", identifier));
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+fn save_capability(capability: &Capability) -> Result<(), String> {
+    ensure_dirs()?;
+    let json = serde_json::to_string_pretty(capability).map_err(|e| e.to_string())?;
+    fs::write(capability_path(&capability.identifier), json).map_err(|e| e.to_string())
+}
+
+fn session_binding_path(session_id: &str) -> PathBuf {
+    get_sessions_dir().join(format!("{}.json", session_id))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SessionBinding {
+    capability_identifier: String,
+}
+
+#[tauri::command]
+pub fn capability_new(identifier: String) -> Result<Capability, String> {
+    let capability = Capability {
+        identifier: identifier.clone(),
+        allowed_skills: Vec::new(),
+        allowed_commands: Vec::new(),
+    };
+    save_capability(&capability)?;
+    Ok(capability)
+}
+
+#[tauri::command]
+pub fn permission_add(identifier: String, skill_id: Option<String>, command: Option<String>) -> Result<Capability, String> {
+    let mut capability = load_capability(&identifier)?;
+
+    if let Some(skill_id) = skill_id {
+        if !capability.allowed_skills.contains(&skill_id) {
+            capability.allowed_skills.push(skill_id);
+        }
+    }
+    if let Some(command) = command {
+        if !capability.allowed_commands.contains(&command) {
+            capability.allowed_commands.push(command);
+        }
+    }
+
+    save_capability(&capability)?;
+    Ok(capability)
+}
+
+#[tauri::command]
+pub fn permission_rm(identifier: String, skill_id: Option<String>, command: Option<String>) -> Result<Capability, String> {
+    let mut capability = load_capability(&identifier)?;
+
+    if let Some(skill_id) = skill_id {
+        capability.allowed_skills.retain(|s| s != &skill_id);
+    }
+    if let Some(command) = command {
+        capability.allowed_commands.retain(|c| c != &command);
+    }
+
+    save_capability(&capability)?;
+    Ok(capability)
+}
+
+#[tauri::command]
+pub fn permission_ls() -> Result<Vec<Capability>, String> {
+    ensure_dirs()?;
+    let mut capabilities = Vec::new();
+
+    let entries = fs::read_dir(get_capabilities_dir()).map_err(|e| e.to_string())?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().map_or(false, |ext| ext == "json") {
+            if let Ok(content) = fs::read_to_string(&path) {
+                if let Ok(capability) = serde_json::from_str::<Capability>(&content) {
+                    capabilities.push(capability);
+                }
+            }
+        }
+    }
+
+    capabilities.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+    Ok(capabilities)
+}
+
+/// Binds `identifier`'s capability to `session_id`, so future
+/// `check_skill_allowed` calls for that session are judged against it.
+#[tauri::command]
+pub fn set_session_capability(session_id: String, identifier: String) -> Result<(), String> {
+    ensure_dirs()?;
+    load_capability(&identifier)?;
+    let binding = SessionBinding { capability_identifier: identifier };
+    let json = serde_json::to_string_pretty(&binding).map_err(|e| e.to_string())?;
+    fs::write(session_binding_path(&session_id), json).map_err(|e| e.to_string())
+}
+
+fn session_capability(session_id: &str) -> Option<Capability> {
+    let path = session_binding_path(session_id);
+    let content = fs::read_to_string(path).ok()?;
+    let binding: SessionBinding = serde_json::from_str(&content).ok()?;
+    load_capability(&binding.capability_identifier).ok()
+}
+
+/// The gate every skill-execution path must call before running a skill.
+/// Skills outside `RESTRICTED_SKILL_IDS` are always allowed; restricted
+/// skills require the session's bound capability to list `skill_id` in
+/// `allowed_skills`.
+#[tauri::command]
+pub fn check_skill_allowed(session_id: String, skill_id: String) -> Result<(), String> {
+    if !RESTRICTED_SKILL_IDS.contains(&skill_id.as_str()) {
+        return Ok(());
+    }
+
+    let capability = session_capability(&session_id)
+        .ok_or_else(|| format!("Skill '{}' is restricted and session '{}' has no capability bound", skill_id, session_id))?;
+
+    if capability.allowed_skills.iter().any(|s| s == &skill_id) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Skill '{}' denied: not granted by capability '{}'",
+            skill_id, capability.identifier
+        ))
+    }
+}
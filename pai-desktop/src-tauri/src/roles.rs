@@ -0,0 +1,190 @@
+use crate::{AppState, Role};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::State;
+
+pub fn get_roles_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("PAI")
+        .join("roles")
+}
+
+pub fn get_all_roles() -> Vec<Role> {
+    let mut roles = get_builtin_roles();
+
+    if let Ok(custom) = get_custom_roles() {
+        roles.extend(custom);
+    }
+
+    roles
+}
+
+/// Analogous to aichat's `SHELL_ROLE`/`CODE_ROLE`/`EXPLAIN_SHELL_ROLE` — a
+/// reusable system-prompt template that seeds a session, as opposed to a
+/// `Skill`, which describes a capability.
+pub fn get_builtin_roles() -> Vec<Role> {
+    vec![
+        Role {
+            id: "shell".to_string(),
+            name: "Shell".to_string(),
+            prompt: "You are a shell command assistant. Given a task, respond with ONLY the shell command(s) \
+                that accomplish it, no explanation and no markdown code fences, unless the user explicitly asks \
+                for an explanation."
+                .to_string(),
+            model: None,
+            temperature: Some(0.0),
+        },
+        Role {
+            id: "code".to_string(),
+            name: "Code".to_string(),
+            prompt: "You are a code generation assistant. Respond with ONLY the code that accomplishes the \
+                task, in a single fenced code block, with no surrounding explanation unless explicitly asked."
+                .to_string(),
+            model: None,
+            temperature: Some(0.0),
+        },
+        Role {
+            id: "explain".to_string(),
+            name: "Explain".to_string(),
+            prompt: "You are a command explainer. Given a shell command, explain concisely what it does, \
+                flagging any destructive or irreversible effects."
+                .to_string(),
+            model: None,
+            temperature: Some(0.3),
+        },
+    ]
+}
+
+fn get_custom_roles() -> Result<Vec<Role>, String> {
+    let roles_dir = get_roles_dir();
+
+    if !roles_dir.exists() {
+        fs::create_dir_all(&roles_dir).map_err(|e| e.to_string())?;
+        return Ok(Vec::new());
+    }
+
+    let mut roles = Vec::new();
+
+    let entries = fs::read_dir(&roles_dir).map_err(|e| e.to_string())?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().map_or(false, |ext| ext == "md") {
+            if let Ok(content) = fs::read_to_string(&path) {
+                if let Some(role) = parse_role_file(&path, &content) {
+                    roles.push(role);
+                }
+            }
+        }
+    }
+
+    Ok(roles)
+}
+
+/// The YAML frontmatter shape a role's `.md` file deserializes into.
+/// `name` defaults to the file stem so a frontmatter-less role file still
+/// parses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RoleFrontmatter {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    temperature: Option<f32>,
+}
+
+/// Splits `content` into its YAML frontmatter and body on the *second* `---`
+/// delimiter. A naive `content.find("---")` (the same fix as in `skills.rs`)
+/// would instead match the opening delimiter itself.
+fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    if let Some(rest) = content.strip_prefix("---") {
+        let rest = rest.strip_prefix('\n').unwrap_or(rest);
+        if let Some(end) = rest.find("\n---") {
+            let frontmatter = &rest[..end];
+            let body = rest[end + 4..].trim_start();
+            return (Some(frontmatter), body);
+        }
+    }
+    (None, content)
+}
+
+fn parse_role_file(path: &PathBuf, content: &str) -> Option<Role> {
+    let id = path.file_stem()?.to_str()?.to_string();
+    let (frontmatter, body) = split_frontmatter(content);
+
+    let meta = frontmatter.and_then(|fm| serde_yaml::from_str::<RoleFrontmatter>(fm).ok());
+
+    Some(Role {
+        name: meta.as_ref().and_then(|m| m.name.clone()).unwrap_or_else(|| id.clone()),
+        prompt: if frontmatter.is_some() { body.to_string() } else { content.to_string() },
+        model: meta.as_ref().and_then(|m| m.model.clone()),
+        temperature: meta.and_then(|m| m.temperature),
+        id,
+    })
+}
+
+#[tauri::command]
+pub fn get_roles() -> Vec<Role> {
+    get_all_roles()
+}
+
+#[tauri::command]
+pub fn save_role(id: String, name: String, model: Option<String>, temperature: Option<f32>, prompt: String) -> Result<(), String> {
+    let roles_dir = get_roles_dir();
+    fs::create_dir_all(&roles_dir).map_err(|e| e.to_string())?;
+
+    let frontmatter = RoleFrontmatter {
+        name: Some(name),
+        model,
+        temperature,
+    };
+    let yaml = serde_yaml::to_string(&frontmatter).map_err(|e| e.to_string())?;
+    let role_content = format!("---\n{}---\n\n{}", yaml, prompt);
+
+    let path = roles_dir.join(format!("{}.md", id));
+    fs::write(&path, role_content).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_role_content(id: String) -> Result<String, String> {
+    let roles_dir = get_roles_dir();
+    let path = roles_dir.join(format!("{}.md", id));
+
+    if path.exists() {
+        fs::read_to_string(&path).map_err(|e| e.to_string())
+    } else {
+        Err("Role not found".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_role(id: String) -> Result<(), String> {
+    let roles_dir = get_roles_dir();
+    let path = roles_dir.join(format!("{}.md", id));
+
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| e.to_string())
+    } else {
+        Err("Role not found".to_string())
+    }
+}
+
+/// Binds `role_id` to `session_id` by injecting the role's prompt as the
+/// session's pinned system message, so `session::compact_messages` never
+/// drops it as the transcript grows.
+#[tauri::command]
+pub fn set_session_role(state: State<'_, AppState>, session_id: String, role_id: String) -> Result<(), String> {
+    let role = get_all_roles()
+        .into_iter()
+        .find(|r| r.id == role_id)
+        .ok_or_else(|| format!("Role {} not found", role_id))?;
+
+    crate::session::append_message(state, session_id, "system".to_string(), role.prompt, true)?;
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use crate::MemoryItem;
+use std::collections::HashMap;
+
+/// BM25 free parameters; `k1` controls term-frequency saturation, `b` controls
+/// how much document length normalizes the score. Standard defaults.
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+
+/// Fuzzy-match hits score at half an exact match, so a typo never outranks
+/// a real one.
+const FUZZY_WEIGHT: f32 = 0.5;
+
+const STOPWORDS: &[&str] = &[
+    "the", "a", "an", "and", "or", "of", "to", "in", "is", "it", "that", "this", "for", "on",
+    "with", "as", "at", "by", "be", "was", "were",
+];
+
+/// Lowercases and splits on non-alphanumeric boundaries, dropping stopwords
+/// and empty tokens left behind by consecutive delimiters.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|t| t.to_lowercase())
+        .filter(|t| !t.is_empty() && !STOPWORDS.contains(&t.as_str()))
+        .collect()
+}
+
+struct Document {
+    memory_id: String,
+    term_freq: HashMap<String, usize>,
+    length: usize,
+}
+
+/// An inverted index over a set of `MemoryItem`s, built fresh per search so
+/// results always reflect the current memory set without needing
+/// invalidation bookkeeping.
+pub struct SearchIndex {
+    documents: Vec<Document>,
+    postings: HashMap<String, Vec<usize>>,
+    avg_doc_len: f32,
+}
+
+impl SearchIndex {
+    /// Tokenizes title, content, and tags into one term-frequency document
+    /// per memory, plus a `term -> document indices` postings map.
+    pub fn build(memories: &[MemoryItem]) -> Self {
+        let mut documents = Vec::with_capacity(memories.len());
+        let mut postings: HashMap<String, Vec<usize>> = HashMap::new();
+
+        for memory in memories {
+            let mut terms = tokenize(&memory.title);
+            terms.extend(tokenize(&memory.content));
+            for tag in &memory.tags {
+                terms.extend(tokenize(tag));
+            }
+
+            let mut term_freq: HashMap<String, usize> = HashMap::new();
+            for term in &terms {
+                *term_freq.entry(term.clone()).or_insert(0) += 1;
+            }
+
+            let doc_index = documents.len();
+            for term in term_freq.keys() {
+                postings.entry(term.clone()).or_default().push(doc_index);
+            }
+
+            documents.push(Document {
+                memory_id: memory.id.clone(),
+                length: terms.len(),
+                term_freq,
+            });
+        }
+
+        let avg_doc_len = if documents.is_empty() {
+            0.0
+        } else {
+            documents.iter().map(|d| d.length as f32).sum::<f32>() / documents.len() as f32
+        };
+
+        Self { documents, postings, avg_doc_len }
+    }
+
+    /// Scores every indexed memory against `query` with BM25, summing
+    /// per-term contributions across exact and typo-tolerant matches, and
+    /// returns `(memory_id, score)` pairs sorted by descending score.
+    pub fn search(&self, query: &str) -> Vec<(String, f32)> {
+        let query_terms = tokenize(query);
+        if query_terms.is_empty() || self.documents.is_empty() {
+            return Vec::new();
+        }
+
+        let n = self.documents.len() as f32;
+        let avg_doc_len = self.avg_doc_len.max(1.0);
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+
+        for term in &query_terms {
+            for (matched_term, weight) in self.match_terms(term) {
+                let Some(doc_indices) = self.postings.get(&matched_term) else {
+                    continue;
+                };
+                let df = doc_indices.len() as f32;
+                let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+                for &doc_index in doc_indices {
+                    let doc = &self.documents[doc_index];
+                    let tf = *doc.term_freq.get(&matched_term).unwrap_or(&0) as f32;
+                    if tf == 0.0 {
+                        continue;
+                    }
+                    let doc_len = doc.length as f32;
+                    let denom = tf + K1 * (1.0 - B + B * doc_len / avg_doc_len);
+                    let score = idf * (tf * (K1 + 1.0)) / denom * weight;
+                    *scores.entry(doc_index).or_insert(0.0) += score;
+                }
+            }
+        }
+
+        let mut ranked: Vec<(String, f32)> = scores
+            .into_iter()
+            .map(|(doc_index, score)| (self.documents[doc_index].memory_id.clone(), score))
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked
+    }
+
+    /// An exact postings hit for `term` if one exists; otherwise every
+    /// indexed term within Levenshtein distance ≤1 (≤2 once `term` is longer
+    /// than 7 characters), each weighted at `FUZZY_WEIGHT`.
+    fn match_terms(&self, term: &str) -> Vec<(String, f32)> {
+        if self.postings.contains_key(term) {
+            return vec![(term.to_string(), 1.0)];
+        }
+
+        let max_distance = if term.chars().count() > 7 { 2 } else { 1 };
+        self.postings
+            .keys()
+            .filter(|candidate| levenshtein(term, candidate) <= max_distance)
+            .map(|candidate| (candidate.clone(), FUZZY_WEIGHT))
+            .collect()
+    }
+}
+
+/// Classic O(len(a)·len(b)) edit-distance DP. Per-query vocabularies are
+/// small enough that this isn't worth a faster bounded automaton.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut dp = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in dp.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=b.len() {
+        dp[0][j] = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            dp[i][j] = (dp[i - 1][j] + 1).min(dp[i][j - 1] + 1).min(dp[i - 1][j - 1] + cost);
+        }
+    }
+
+    dp[a.len()][b.len()]
+}
@@ -1,7 +1,9 @@
+use crate::AppState;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
@@ -10,6 +12,184 @@ pub struct Session {
     pub created_at: i64,
     pub last_active: i64,
     pub message_count: usize,
+    /// Temp sessions (aichat's `TEMP_SESSION_NAME` equivalent), created by
+    /// `create_temp_session`, live only in `AppState::temp_session` and skip
+    /// every `fs::write` in `update_session_activity`/`append_message` until
+    /// `promote_temp_session` flushes them to disk.
+    #[serde(default)]
+    pub ephemeral: bool,
+}
+
+/// Temp session ids are minted as `temp-<millis>` by `create_temp_session`,
+/// distinguishing them from `session-<millis>` without needing an extra
+/// lookup to know whether a given id lives on disk or only in `AppState`.
+fn is_temp_session_id(session_id: &str) -> bool {
+    session_id.starts_with("temp-")
+}
+
+/// One turn of a session's durable transcript, persisted in the sidecar
+/// `<session_id>-messages.json` alongside `session-*.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub role: String,
+    pub content: String,
+    pub timestamp: i64,
+    /// Pinned messages (e.g. a system/role prompt) are never dropped by
+    /// `compact_messages`, regardless of the token budget.
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Token budget for a session's stored transcript. Exceeding it makes
+/// `append_message` drop the oldest non-pinned messages via `compact_messages`.
+const DEFAULT_MAX_CONTEXT_TOKENS: usize = 8_000;
+
+/// Rough chars/4 token estimate, matching aichat-style budgeting without
+/// pulling a full tokenizer into a hot path that runs on every append.
+fn estimate_tokens(text: &str) -> usize {
+    (text.chars().count() / 4).max(1)
+}
+
+/// Trims `messages` to fit `max_context_tokens`, dropping the oldest
+/// non-pinned messages first and always keeping pinned ones. Returns the
+/// trimmed vector plus whether anything was actually dropped, so callers
+/// can surface a "context trimmed" indicator.
+pub fn compact_messages(messages: Vec<Message>, max_context_tokens: usize) -> (Vec<Message>, bool) {
+    let original_len = messages.len();
+    let total: usize = messages.iter().map(|m| estimate_tokens(&m.content)).sum();
+    if total <= max_context_tokens {
+        return (messages, false);
+    }
+
+    let (pinned, rest): (Vec<Message>, Vec<Message>) = messages.into_iter().partition(|m| m.pinned);
+    let pinned_tokens: usize = pinned.iter().map(|m| estimate_tokens(&m.content)).sum();
+    let mut budget = max_context_tokens.saturating_sub(pinned_tokens);
+
+    let mut kept_rest = Vec::new();
+    for message in rest.into_iter().rev() {
+        let tokens = estimate_tokens(&message.content);
+        if tokens > budget {
+            break;
+        }
+        budget -= tokens;
+        kept_rest.push(message);
+    }
+    kept_rest.reverse();
+
+    let mut result = pinned;
+    result.extend(kept_rest);
+    result.sort_by_key(|m| m.timestamp);
+
+    let truncated = result.len() < original_len;
+    (result, truncated)
+}
+
+fn get_messages_file(session_id: &str) -> PathBuf {
+    get_sessions_dir().join(format!("{}-messages.json", session_id))
+}
+
+fn load_messages_internal(session_id: &str) -> Result<Vec<Message>, String> {
+    ensure_sessions_dir()?;
+    let path = get_messages_file(session_id);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+fn save_messages_internal(session_id: &str, messages: &[Message]) -> Result<(), String> {
+    let path = get_messages_file(session_id);
+    let json = serde_json::to_string_pretty(messages).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| e.to_string())
+}
+
+/// Appends one message to `session_id`'s transcript, compacts it against
+/// `DEFAULT_MAX_CONTEXT_TOKENS`, persists the result, and bumps the
+/// session's `message_count`. Returns the (possibly trimmed) transcript and
+/// whether compaction actually dropped anything.
+#[tauri::command]
+pub fn append_message(
+    state: State<'_, AppState>,
+    session_id: String,
+    role: String,
+    content: String,
+    pinned: bool,
+) -> Result<(Vec<Message>, bool), String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_millis() as i64;
+    let message = Message { role, content, timestamp: now, pinned };
+
+    if is_temp_session_id(&session_id) {
+        let mut temp = state.temp_session.lock().map_err(|e| e.to_string())?;
+        return match temp.as_mut() {
+            Some((session, messages)) if session.id == session_id => {
+                messages.push(message);
+                let (compacted, truncated) =
+                    compact_messages(std::mem::take(messages), DEFAULT_MAX_CONTEXT_TOKENS);
+                *messages = compacted.clone();
+                session.message_count += 1;
+                Ok((compacted, truncated))
+            }
+            _ => Err("Temp session not found".to_string()),
+        };
+    }
+
+    ensure_sessions_dir()?;
+    let mut messages = load_messages_internal(&session_id)?;
+    messages.push(message);
+
+    let (compacted, truncated) = compact_messages(messages, DEFAULT_MAX_CONTEXT_TOKENS);
+    save_messages_internal(&session_id, &compacted)?;
+
+    let session_file = get_sessions_dir().join(format!("{}.json", session_id));
+    if let Ok(content) = fs::read_to_string(&session_file) {
+        if let Ok(mut session) = serde_json::from_str::<Session>(&content) {
+            let _ = increment_message_count(&mut session);
+        }
+    }
+
+    Ok((compacted, truncated))
+}
+
+/// Named `get_session_messages`/`clear_session_messages` (rather than the
+/// bare `get_messages`/`clear_messages` an aichat-style API would suggest)
+/// because those names are already taken by `messages::get_messages` and
+/// `messages::clear_messages` in the global chat-history store.
+#[tauri::command]
+pub fn get_session_messages(state: State<'_, AppState>, session_id: String) -> Result<Vec<Message>, String> {
+    if is_temp_session_id(&session_id) {
+        let temp = state.temp_session.lock().map_err(|e| e.to_string())?;
+        return match temp.as_ref() {
+            Some((session, messages)) if session.id == session_id => Ok(messages.clone()),
+            _ => Ok(Vec::new()),
+        };
+    }
+
+    load_messages_internal(&session_id)
+}
+
+#[tauri::command]
+pub fn clear_session_messages(state: State<'_, AppState>, session_id: String) -> Result<(), String> {
+    if is_temp_session_id(&session_id) {
+        let mut temp = state.temp_session.lock().map_err(|e| e.to_string())?;
+        if let Some((session, messages)) = temp.as_mut() {
+            if session.id == session_id {
+                messages.clear();
+                session.message_count = 0;
+            }
+        }
+        return Ok(());
+    }
+
+    ensure_sessions_dir()?;
+    let path = get_messages_file(&session_id);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
 }
 
 pub fn get_sessions_dir() -> PathBuf {
@@ -61,8 +241,9 @@ pub fn create_new_session(name: String) -> Result<Session, String> {
         created_at: now,
         last_active: now,
         message_count: 0,
+        ephemeral: false,
     };
-    
+
     let sessions_dir = get_sessions_dir();
     let session_file = sessions_dir.join(format!("{}.json", session.id));
     
@@ -80,9 +261,15 @@ pub fn update_session_activity(session: &mut Session) -> Result<(), String> {
         .duration_since(UNIX_EPOCH)
         .map_err(|e| e.to_string())?
         .as_millis() as i64;
-    
+
     session.last_active = now;
-    
+
+    // Temp sessions have no file to update; `create_temp_session`/
+    // `append_message` keep the in-memory copy in `AppState` current instead.
+    if session.ephemeral {
+        return Ok(());
+    }
+
     let sessions_dir = get_sessions_dir();
     let session_file = sessions_dir.join(format!("{}.json", session.id));
     
@@ -100,6 +287,62 @@ pub fn increment_message_count(session: &mut Session) -> Result<(), String> {
     update_session_activity(session)
 }
 
+/// Creates an in-memory-only session (aichat's `TEMP_SESSION_NAME`
+/// equivalent) held in `AppState::temp_session` instead of under
+/// `get_sessions_dir()`, so throwaway experiments never litter the
+/// sessions directory. Replaces any previously uncommitted temp session.
+#[tauri::command]
+pub fn create_temp_session(state: State<'_, AppState>) -> Result<Session, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_millis() as i64;
+
+    let session = Session {
+        id: format!("temp-{}", now),
+        name: "Temporary".to_string(),
+        created_at: now,
+        last_active: now,
+        message_count: 0,
+        ephemeral: true,
+    };
+
+    let mut temp = state.temp_session.lock().map_err(|e| e.to_string())?;
+    *temp = Some((session.clone(), Vec::new()));
+
+    Ok(session)
+}
+
+/// Flushes the current temp session to disk as an ordinary session named
+/// `name`, carrying its transcript over via `save_messages_internal`.
+#[tauri::command]
+pub fn promote_temp_session(state: State<'_, AppState>, name: String) -> Result<Session, String> {
+    let (_, messages) = state
+        .temp_session
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or_else(|| "No temp session to promote".to_string())?;
+
+    let session = create_new_session(name)?;
+    if messages.is_empty() {
+        return Ok(session);
+    }
+
+    save_messages_internal(&session.id, &messages)?;
+
+    let session_file = get_sessions_dir().join(format!("{}.json", session.id));
+    let content = fs::read_to_string(&session_file).map_err(|e| e.to_string())?;
+    let mut session: Session = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    session.message_count = messages.len();
+    let json = serde_json::to_string_pretty(&session).map_err(|e| e.to_string())?;
+    fs::write(&session_file, json).map_err(|e| e.to_string())?;
+
+    Ok(session)
+}
+
+/// Only scans `get_sessions_dir()`, so temp sessions — which never touch
+/// disk until `promote_temp_session` — are excluded automatically.
 #[tauri::command]
 pub fn list_sessions() -> Result<Vec<Session>, String> {
     ensure_sessions_dir()?;
@@ -195,6 +438,146 @@ pub fn rename_session(session_id: String, new_name: String) -> Result<Session, S
             fs::write(&current_file, &json).map_err(|e| e.to_string())?;
         }
     }
-    
+
+    Ok(session)
+}
+
+/// Minimal YAML header `export_session` writes atop the Markdown transcript
+/// and `import_session` reads back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ExportHeader {
+    id: String,
+    name: String,
+    created_at: i64,
+}
+
+/// Writes `session_id`'s metadata and message history to `path` as a
+/// human-readable Markdown transcript (aichat-style `messages.md`), so it
+/// can be archived, shared, or version-controlled outside the JSON store.
+#[tauri::command]
+pub fn export_session(session_id: String, path: String) -> Result<(), String> {
+    let session_file = get_sessions_dir().join(format!("{}.json", session_id));
+    let content = fs::read_to_string(&session_file).map_err(|e| e.to_string())?;
+    let session: Session = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+    let messages = load_messages_internal(&session_id)?;
+
+    let header = ExportHeader {
+        id: session.id,
+        name: session.name,
+        created_at: session.created_at,
+    };
+    let yaml = serde_yaml::to_string(&header).map_err(|e| e.to_string())?;
+
+    let mut out = format!("---\n{}---\n\n", yaml);
+    for message in &messages {
+        out.push_str(&format!("## {} ({})\n\n{}\n\n", message.role, message.timestamp, escape_markdown_body(&message.content)));
+    }
+
+    fs::write(&path, out).map_err(|e| e.to_string())
+}
+
+/// Escapes any line in `content` that would otherwise be misread as a
+/// `## role (timestamp)` header by `parse_markdown_messages` — an assistant
+/// response containing its own Markdown H2 (e.g. `algorithm::create_plan`'s
+/// `"## Execution Plan"`) would otherwise split one message into two on
+/// export/import. Lines that already start with backslashes before `## ` get
+/// one more, so `unescape_markdown_body` reverses the escape exactly.
+fn escape_markdown_body(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| if line.trim_start_matches('\\').starts_with("## ") { format!("\\{}", line) } else { line.to_string() })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn unescape_markdown_body(line: &str) -> &str {
+    if line.starts_with('\\') && line.trim_start_matches('\\').starts_with("## ") {
+        &line[1..]
+    } else {
+        line
+    }
+}
+
+fn split_export(content: &str) -> (Option<&str>, &str) {
+    if let Some(rest) = content.strip_prefix("---") {
+        let rest = rest.strip_prefix('\n').unwrap_or(rest);
+        if let Some(end) = rest.find("\n---") {
+            let frontmatter = &rest[..end];
+            let body = rest[end + 4..].trim_start();
+            return (Some(frontmatter), body);
+        }
+    }
+    (None, content)
+}
+
+/// Parses a `## role (timestamp)` header line, requiring the exact shape
+/// `export_session` writes. Returns `None` for anything else (a line that
+/// merely starts with `## ` but isn't a real header) so `parse_markdown_messages`
+/// can fold it into the current message's body instead of inventing a new
+/// message with a bogus role and a `0` timestamp.
+fn parse_message_header(header: &str) -> Option<(String, i64)> {
+    let (role, rest) = header.split_once(" (")?;
+    let timestamp = rest.strip_suffix(')')?.parse().ok()?;
+    Some((role.to_string(), timestamp))
+}
+
+/// Parses `## role (timestamp)` blocks written by `export_session` back
+/// into messages. A block's content runs until the next `## ` header or EOF.
+/// Lines escaped by `escape_markdown_body` (content that itself starts with
+/// `## `) are unescaped rather than treated as a new header.
+fn parse_markdown_messages(body: &str) -> Vec<Message> {
+    let mut messages = Vec::new();
+    let mut current: Option<(String, i64)> = None;
+    let mut content = String::new();
+
+    for line in body.lines() {
+        let is_header = line.strip_prefix("## ").and_then(parse_message_header);
+        if let Some((role, timestamp)) = is_header {
+            if let Some((role, timestamp)) = current.take() {
+                messages.push(Message { role, content: content.trim().to_string(), timestamp, pinned: false });
+                content.clear();
+            }
+            current = Some((role, timestamp));
+        } else if current.is_some() {
+            content.push_str(unescape_markdown_body(line));
+            content.push('\n');
+        }
+    }
+    if let Some((role, timestamp)) = current {
+        messages.push(Message { role, content: content.trim().to_string(), timestamp, pinned: false });
+    }
+
+    messages
+}
+
+/// Parses a Markdown transcript written by `export_session` into a brand
+/// new session (fresh id, so importing the same file twice doesn't collide).
+#[tauri::command]
+pub fn import_session(path: String) -> Result<Session, String> {
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let (frontmatter, body) = split_export(&content);
+
+    let name = frontmatter
+        .and_then(|fm| serde_yaml::from_str::<ExportHeader>(fm).ok())
+        .map(|header| header.name)
+        .unwrap_or_else(|| "Imported Session".to_string());
+
+    let session = create_new_session(name)?;
+
+    let messages = parse_markdown_messages(body);
+    if !messages.is_empty() {
+        save_messages_internal(&session.id, &messages)?;
+
+        let session_file = get_sessions_dir().join(format!("{}.json", session.id));
+        if let Ok(content) = fs::read_to_string(&session_file) {
+            if let Ok(mut session) = serde_json::from_str::<Session>(&content) {
+                session.message_count = messages.len();
+                let json = serde_json::to_string_pretty(&session).map_err(|e| e.to_string())?;
+                fs::write(&session_file, json).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
     Ok(session)
 }
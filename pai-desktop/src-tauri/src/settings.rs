@@ -1,4 +1,5 @@
-use crate::{AppState, Settings};
+use crate::{AppState, Settings, CURRENT_SETTINGS_VERSION};
+use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
 use tauri::State;
@@ -13,6 +14,41 @@ fn get_settings_path() -> PathBuf {
     get_config_dir().join("settings.json")
 }
 
+/// One schema migration step: transforms a v`N` settings document into v`N+1`.
+/// `MIGRATIONS[i]` runs when the stored version is `i`; ordered and
+/// append-only, so `CURRENT_SETTINGS_VERSION` always equals `MIGRATIONS.len()`.
+type Migration = fn(Value) -> Value;
+
+/// Legacy settings files predate the `version` field entirely, which
+/// `serde(default)` reads back as version `0`. v1 is the first versioned
+/// schema and didn't otherwise change shape, so this migration only stamps
+/// the field; future schema changes append their own `vN_to_vN+1` here.
+fn v0_to_v1(mut value: Value) -> Value {
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("version".to_string(), Value::from(1));
+    }
+    value
+}
+
+/// v2 adds `embedding_api_url`/`embedding_api_key` for
+/// `memory::search_memories_semantic`; `serde(default)` on those fields
+/// already backfills them, so this migration only stamps the version.
+fn v1_to_v2(mut value: Value) -> Value {
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("version".to_string(), Value::from(2));
+    }
+    value
+}
+
+const MIGRATIONS: &[Migration] = &[v0_to_v1, v1_to_v2];
+
+/// Result of a `load_settings_from_disk_versioned` call, so startup logging
+/// can report whether an on-disk schema upgrade just happened.
+pub struct LoadedSettings {
+    pub settings: Settings,
+    pub migrated: bool,
+}
+
 #[tauri::command]
 pub fn get_settings(state: State<'_, AppState>) -> Result<Settings, String> {
     let settings = state.settings.lock().map_err(|e| e.to_string())?;
@@ -30,20 +66,47 @@ pub fn save_settings(state: State<'_, AppState>, settings: Settings) -> Result<(
     let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
     fs::write(&path, json).map_err(|e| e.to_string())?;
 
+    let mut completion_provider = state.completion_provider.lock().map_err(|e| e.to_string())?;
+    *completion_provider = crate::completion::build_completion_provider(&settings);
+
     let mut state_settings = state.settings.lock().map_err(|e| e.to_string())?;
     *state_settings = settings;
 
     Ok(())
 }
 
-pub fn load_settings_from_disk() -> Result<Settings, String> {
+/// Loads `settings.json`, migrating it forward to `CURRENT_SETTINGS_VERSION`
+/// first if its stored `version` is behind. A pre-migration backup is
+/// written to `settings.json.bak` so an upgrade never drops user
+/// configuration, even if a migration closure has a bug.
+pub fn load_settings_from_disk_versioned() -> Result<LoadedSettings, String> {
     let path = get_settings_path();
-    
+
     if !path.exists() {
-        return Ok(Settings::default());
+        return Ok(LoadedSettings { settings: Settings::default(), migrated: false });
     }
 
     let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    let settings = serde_json::from_str::<Settings>(&content).map_err(|e| e.to_string())?;
-    Ok(settings)
+    let mut value: Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+    let stored_version = value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    let migrated = stored_version < CURRENT_SETTINGS_VERSION;
+
+    if migrated {
+        fs::write(get_config_dir().join("settings.json.bak"), &content).map_err(|e| e.to_string())?;
+
+        for migration in &MIGRATIONS[(stored_version as usize).min(MIGRATIONS.len())..] {
+            value = migration(value);
+        }
+
+        let migrated_json = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+        fs::write(&path, migrated_json).map_err(|e| e.to_string())?;
+    }
+
+    let settings = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    Ok(LoadedSettings { settings, migrated })
+}
+
+pub fn load_settings_from_disk() -> Result<Settings, String> {
+    Ok(load_settings_from_disk_versioned()?.settings)
 }
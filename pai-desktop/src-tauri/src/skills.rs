@@ -1,4 +1,5 @@
 use crate::Skill;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
@@ -11,151 +12,112 @@ pub fn get_skills_dir() -> PathBuf {
 
 pub fn get_all_skills() -> Vec<Skill> {
     let mut skills = get_builtin_skills();
-    
+
     if let Ok(custom) = get_custom_skills() {
         skills.extend(custom);
     }
-    
+
     skills
 }
 
+fn builtin_skill(id: &str, name: &str, description: &str, category: &str) -> Skill {
+    Skill {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        category: category.to_string(),
+        triggers: Vec::new(),
+        model: None,
+        dependencies: Vec::new(),
+        enabled: true,
+    }
+}
+
 pub fn get_builtin_skills() -> Vec<Skill> {
     vec![
-        Skill {
-            id: "agents".to_string(),
-            name: "Agents".to_string(),
-            description: "Dynamic agent composition and management system".to_string(),
-            category: "core".to_string(),
-        },
-        Skill {
-            id: "research".to_string(),
-            name: "Research".to_string(),
-            description: "Comprehensive research, analysis and content extraction".to_string(),
-            category: "core".to_string(),
-        },
-        Skill {
-            id: "telos".to_string(),
-            name: "Telos".to_string(),
-            description: "Life OS and project analysis framework".to_string(),
-            category: "core".to_string(),
-        },
-        Skill {
-            id: "redteam".to_string(),
-            name: "RedTeam".to_string(),
-            description: "Security assessment and red team operations".to_string(),
-            category: "security".to_string(),
-        },
-        Skill {
-            id: "recon".to_string(),
-            name: "Recon".to_string(),
-            description: "Information gathering and reconnaissance".to_string(),
-            category: "security".to_string(),
-        },
-        Skill {
-            id: "osint".to_string(),
-            name: "OSINT".to_string(),
-            description: "Open source intelligence".to_string(),
-            category: "security".to_string(),
-        },
-        Skill {
-            id: "browser".to_string(),
-            name: "Browser".to_string(),
-            description: "Browser automation and control".to_string(),
-            category: "tools".to_string(),
-        },
-        Skill {
-            id: "art".to_string(),
-            name: "Art".to_string(),
-            description: "Art generation and creative tools".to_string(),
-            category: "creative".to_string(),
-        },
-        Skill {
-            id: "documents".to_string(),
-            name: "Documents".to_string(),
-            description: "Document processing (PDF, Docx, Xlsx, Pptx)".to_string(),
-            category: "tools".to_string(),
-        },
-        Skill {
-            id: "apify".to_string(),
-            name: "Apify".to_string(),
-            description: "Web scraping and automation".to_string(),
-            category: "tools".to_string(),
-        },
-        Skill {
-            id: "prompting".to_string(),
-            name: "Prompting".to_string(),
-            description: "Prompt engineering and optimization".to_string(),
-            category: "ai".to_string(),
-        },
-        Skill {
-            id: "fabric".to_string(),
-            name: "Fabric".to_string(),
-            description: "AI patterns library (242+ patterns)".to_string(),
-            category: "ai".to_string(),
-        },
-        Skill {
-            id: "evals".to_string(),
-            name: "Evals".to_string(),
-            description: "Evaluation and testing framework".to_string(),
-            category: "ai".to_string(),
-        },
-        Skill {
-            id: "council".to_string(),
-            name: "Council".to_string(),
-            description: "Multi-agent decision committee".to_string(),
-            category: "ai".to_string(),
-        },
-        Skill {
-            id: "firstprinciples".to_string(),
-            name: "First Principles".to_string(),
-            description: "First principles thinking and analysis".to_string(),
-            category: "ai".to_string(),
-        },
-        Skill {
-            id: "becreative".to_string(),
-            name: "BeCreative".to_string(),
-            description: "Creative brainstorming and ideation".to_string(),
-            category: "creative".to_string(),
-        },
-        Skill {
-            id: "paiupgrade".to_string(),
-            name: "PAI Upgrade".to_string(),
-            description: "Auto upgrade system for PAI".to_string(),
-            category: "system".to_string(),
-        },
-        Skill {
-            id: "createskill".to_string(),
-            name: "CreateSkill".to_string(),
-            description: "Tool for creating custom skills".to_string(),
-            category: "tools".to_string(),
-        },
-        Skill {
-            id: "createcli".to_string(),
-            name: "CreateCLI".to_string(),
-            description: "Tool for creating CLI applications".to_string(),
-            category: "tools".to_string(),
-        },
-        Skill {
-            id: "extractwisdom".to_string(),
-            name: "Extract Wisdom".to_string(),
-            description: "Extract insights and wisdom from content".to_string(),
-            category: "ai".to_string(),
-        },
+        builtin_skill("agents", "Agents", "Dynamic agent composition and management system", "core"),
+        builtin_skill("research", "Research", "Comprehensive research, analysis and content extraction", "core"),
+        builtin_skill("telos", "Telos", "Life OS and project analysis framework", "core"),
+        builtin_skill("redteam", "RedTeam", "Security assessment and red team operations", "security"),
+        builtin_skill("recon", "Recon", "Information gathering and reconnaissance", "security"),
+        builtin_skill("osint", "OSINT", "Open source intelligence", "security"),
+        builtin_skill("browser", "Browser", "Browser automation and control", "tools"),
+        builtin_skill("art", "Art", "Art generation and creative tools", "creative"),
+        builtin_skill("documents", "Documents", "Document processing (PDF, Docx, Xlsx, Pptx)", "tools"),
+        builtin_skill("apify", "Apify", "Web scraping and automation", "tools"),
+        builtin_skill("prompting", "Prompting", "Prompt engineering and optimization", "ai"),
+        builtin_skill("fabric", "Fabric", "AI patterns library (242+ patterns)", "ai"),
+        builtin_skill("evals", "Evals", "Evaluation and testing framework", "ai"),
+        builtin_skill("council", "Council", "Multi-agent decision committee", "ai"),
+        builtin_skill("firstprinciples", "First Principles", "First principles thinking and analysis", "ai"),
+        builtin_skill("becreative", "BeCreative", "Creative brainstorming and ideation", "creative"),
+        builtin_skill("paiupgrade", "PAI Upgrade", "Auto upgrade system for PAI", "system"),
+        builtin_skill("createskill", "CreateSkill", "Tool for creating custom skills", "tools"),
+        builtin_skill("createcli", "CreateCLI", "Tool for creating CLI applications", "tools"),
+        builtin_skill("extractwisdom", "Extract Wisdom", "Extract insights and wisdom from content", "ai"),
     ]
 }
 
+/// The YAML frontmatter shape a skill's `.md` file deserializes into.
+/// `name` is required; everything else defaults so existing
+/// skill files without the newer fields still parse.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SkillFrontmatter {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default = "default_category")]
+    category: String,
+    #[serde(default)]
+    triggers: Vec<String>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+}
+
+fn default_category() -> String {
+    "custom".to_string()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Splits `content` into its YAML frontmatter and body on the *second*
+/// `---` delimiter (the first commit's `content.find("---")` matched the
+/// opening delimiter itself and never worked). Returns `None` frontmatter
+/// for plain-text skill files.
+fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    if let Some(rest) = content.strip_prefix("---") {
+        let rest = rest.strip_prefix('\n').unwrap_or(rest);
+        if let Some(end) = rest.find("\n---") {
+            let frontmatter = &rest[..end];
+            let body = rest[end + 4..].trim_start();
+            return (Some(frontmatter), body);
+        }
+    }
+    (None, content)
+}
+
+fn parse_frontmatter(frontmatter: &str) -> Result<SkillFrontmatter, String> {
+    serde_yaml::from_str(frontmatter).map_err(|e| format!("Invalid skill frontmatter: {}", e))
+}
+
 fn get_custom_skills() -> Result<Vec<Skill>, String> {
     let skills_dir = get_skills_dir();
-    
+
     if !skills_dir.exists() {
         fs::create_dir_all(&skills_dir).map_err(|e| e.to_string())?;
         return Ok(Vec::new());
     }
 
     let mut skills = Vec::new();
-    
+
     let entries = fs::read_dir(&skills_dir).map_err(|e| e.to_string())?;
-    
+
     for entry in entries.flatten() {
         let path = entry.path();
         if path.extension().map_or(false, |ext| ext == "md") || path.extension().map_or(false, |ext| ext == "yaml") {
@@ -172,39 +134,35 @@ fn get_custom_skills() -> Result<Vec<Skill>, String> {
 
 fn parse_skill_file(path: &PathBuf, content: &str) -> Option<Skill> {
     let id = path.file_stem()?.to_str()?.to_string();
-    
-    let mut name = id.clone();
-    let mut description = String::new();
-    let mut category = "custom".to_string();
-    
-    if content.starts_with("---") {
-        if let Some(end) = content.find("---") {
-            let frontmatter = &content[3..end];
-            for line in frontmatter.lines() {
-                let line = line.trim();
-                if line.starts_with("name:") {
-                    name = line[5..].trim().to_string();
-                } else if line.starts_with("description:") {
-                    description = line[12..].trim().to_string();
-                } else if line.starts_with("category:") {
-                    category = line[9..].trim().to_string();
-                }
-            }
-        }
-    } else {
-        if let Some(first_line) = content.lines().next() {
-            if first_line.starts_with("# ") {
-                name = first_line[2..].trim().to_string();
-            }
-        }
-        description = content.lines().skip(1).take(2).collect::<Vec<_>>().join(" ");
-    }
+    let (frontmatter, body) = split_frontmatter(content);
+
+    let meta = match frontmatter {
+        Some(frontmatter) => parse_frontmatter(frontmatter).ok()?,
+        None => SkillFrontmatter {
+            name: content
+                .lines()
+                .next()
+                .map(|line| line.trim_start_matches("# ").trim().to_string())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| id.clone()),
+            description: body.lines().skip(1).take(2).collect::<Vec<_>>().join(" "),
+            category: default_category(),
+            triggers: Vec::new(),
+            model: None,
+            dependencies: Vec::new(),
+            enabled: true,
+        },
+    };
 
     Some(Skill {
         id,
-        name,
-        description,
-        category,
+        name: meta.name,
+        description: meta.description,
+        category: meta.category,
+        triggers: meta.triggers,
+        model: meta.model,
+        dependencies: meta.dependencies,
+        enabled: meta.enabled,
     })
 }
 
@@ -213,15 +171,33 @@ pub fn get_skills() -> Vec<Skill> {
     get_all_skills()
 }
 
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
-pub fn save_skill(id: String, name: String, description: String, category: String, content: String) -> Result<(), String> {
+pub fn save_skill(
+    id: String,
+    name: String,
+    description: String,
+    category: String,
+    triggers: Vec<String>,
+    model: Option<String>,
+    dependencies: Vec<String>,
+    enabled: bool,
+    content: String,
+) -> Result<(), String> {
     let skills_dir = get_skills_dir();
     fs::create_dir_all(&skills_dir).map_err(|e| e.to_string())?;
 
-    let skill_content = format!(
-        "---\nname: {}\ndescription: {}\ncategory: {}\n---\n\n{}",
-        name, description, category, content
-    );
+    let frontmatter = SkillFrontmatter {
+        name,
+        description,
+        category,
+        triggers,
+        model,
+        dependencies,
+        enabled,
+    };
+    let yaml = serde_yaml::to_string(&frontmatter).map_err(|e| e.to_string())?;
+    let skill_content = format!("---\n{}---\n\n{}", yaml, content);
 
     let path = skills_dir.join(format!("{}.md", id));
     fs::write(&path, skill_content).map_err(|e| e.to_string())?;
@@ -233,7 +209,7 @@ pub fn save_skill(id: String, name: String, description: String, category: Strin
 pub fn get_skill_content(id: String) -> Result<String, String> {
     let skills_dir = get_skills_dir();
     let path = skills_dir.join(format!("{}.md", id));
-    
+
     if path.exists() {
         fs::read_to_string(&path).map_err(|e| e.to_string())
     } else {
@@ -245,10 +221,32 @@ pub fn get_skill_content(id: String) -> Result<String, String> {
 pub fn delete_skill(id: String) -> Result<(), String> {
     let skills_dir = get_skills_dir();
     let path = skills_dir.join(format!("{}.md", id));
-    
+
     if path.exists() {
         fs::remove_file(&path).map_err(|e| e.to_string())
     } else {
         Err("Skill not found".to_string())
     }
 }
+
+/// Parses `content` as a skill file without touching disk, so the editor
+/// can surface malformed YAML frontmatter before `save_skill` is called.
+#[tauri::command]
+pub fn validate_skill(content: String) -> Result<Skill, String> {
+    let (frontmatter, _body) = split_frontmatter(&content);
+    let frontmatter = frontmatter.ok_or_else(|| {
+        "Missing YAML frontmatter: expected content to start with `---` and contain a closing `---`".to_string()
+    })?;
+    let meta = parse_frontmatter(frontmatter)?;
+
+    Ok(Skill {
+        id: String::new(),
+        name: meta.name,
+        description: meta.description,
+        category: meta.category,
+        triggers: meta.triggers,
+        model: meta.model,
+        dependencies: meta.dependencies,
+        enabled: meta.enabled,
+    })
+}
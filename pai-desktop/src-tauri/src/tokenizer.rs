@@ -0,0 +1,43 @@
+use tiktoken_rs::CoreBPE;
+
+/// Which end of the text to cut from when it has to be shortened to fit a
+/// token budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruncateDirection {
+    /// Drop tokens from the front, keeping the tail — used for the oldest
+    /// message in a history window, so the most recent part of it survives.
+    Start,
+    /// Drop tokens from the back, keeping the head.
+    End,
+}
+
+/// Looks up the BPE table for `model`, falling back to `o200k_base` (and to
+/// `cl100k_base` only if that table fails to load) when the model is unknown.
+fn encoding_for_model(model: &str) -> CoreBPE {
+    tiktoken_rs::get_bpe_from_model(model)
+        .or_else(|_| tiktoken_rs::o200k_base())
+        .or_else(|_| tiktoken_rs::cl100k_base())
+        .expect("built-in tiktoken encoding tables should always load")
+}
+
+/// Counts how many tokens `text` encodes to under `model`'s tokenizer.
+pub fn count_tokens(model: &str, text: &str) -> usize {
+    encoding_for_model(model).encode_with_special_tokens(text).len()
+}
+
+/// Shortens `text` to at most `max_tokens`, cutting from `direction`.
+/// Returns `text` unchanged if it already fits.
+pub fn truncate(model: &str, text: &str, max_tokens: usize, direction: TruncateDirection) -> String {
+    let bpe = encoding_for_model(model);
+    let tokens = bpe.encode_with_special_tokens(text);
+    if tokens.len() <= max_tokens {
+        return text.to_string();
+    }
+
+    let kept = match direction {
+        TruncateDirection::Start => &tokens[tokens.len() - max_tokens..],
+        TruncateDirection::End => &tokens[..max_tokens],
+    };
+
+    bpe.decode(kept.to_vec()).unwrap_or_default()
+}